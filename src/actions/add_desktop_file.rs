@@ -0,0 +1,55 @@
+use crate::actions::flatpak::{insert_with_artwork, select_actions};
+use crate::config::{AddDesktopFile, Config};
+use crate::desktop_file::{create_desktop_file_shortcut, find_desktop_file, read_application};
+
+pub(crate) fn add_desktop_file(config: &Config, add_desktop_file_config: &AddDesktopFile) -> bool {
+    let data_dirs = config.get_data_dirs();
+    let desktop_file_path =
+        match find_desktop_file(&add_desktop_file_config.desktop_file, &data_dirs) {
+            Ok(value) => value,
+            Err(err) => {
+                println!("{err}");
+                return false;
+            }
+        };
+    let Some(flatpak_paths) = config.get_flatpak_paths() else {
+        println!("Could not find the home folder");
+        return false;
+    };
+    let desktop_file = match read_application(&desktop_file_path, config.get_locale().as_deref()) {
+        Ok(value) => value,
+        Err(err) => {
+            println!(
+                "Failed to create a shortcut for {} - {err}",
+                desktop_file_path.display()
+            );
+            return false;
+        }
+    };
+    let Some(actions) = select_actions(&desktop_file, &add_desktop_file_config.actions) else {
+        return false;
+    };
+
+    let mut shortcuts = Vec::new();
+    for action in std::iter::once(None).chain(actions.into_iter().map(Some)) {
+        match create_desktop_file_shortcut(
+            &desktop_file_path,
+            &desktop_file,
+            action,
+            &data_dirs,
+            &flatpak_paths.icon_store,
+            flatpak_paths.icon_theme.as_deref(),
+            config.app_id_algorithm,
+        ) {
+            Ok(value) => shortcuts.push(value),
+            Err(err) => {
+                println!(
+                    "Failed to create a shortcut for {} - {err}",
+                    desktop_file_path.display()
+                );
+                return false;
+            }
+        }
+    }
+    insert_with_artwork(config, shortcuts)
+}
@@ -0,0 +1,114 @@
+use crate::config::{AddShortcut, Artwork, Config};
+use crate::icons::to_steam_icon;
+use crate::shortcuts::shortcut::ShortcutEntry;
+use crate::utils::{
+    create_grid_for_shortcut, find_executable, quote_path, try_download_grid_files,
+};
+use std::path::Path;
+
+pub(crate) fn add_shortcut(config: &Config, add_shortcut_config: &AddShortcut) -> bool {
+    let exe = match find_executable(&add_shortcut_config.exe) {
+        Ok(value) => value,
+        Err(err) => {
+            println!("Invalid executable - {err}");
+            return false;
+        }
+    };
+    let start_dir = match &add_shortcut_config.start_dir {
+        Some(start_dir) if !start_dir.is_dir() => {
+            println!("Start dir {} is not a directory", start_dir.display());
+            return false;
+        }
+        Some(start_dir) => start_dir.canonicalize().unwrap_or(start_dir.clone()),
+        None => exe.parent().unwrap_or(Path::new("/")).to_path_buf(),
+    };
+    let icon = match &add_shortcut_config.icon {
+        Some(icon) if !icon.is_file() => {
+            println!("Icon {} does not exist", icon.display());
+            return false;
+        }
+        Some(icon) => {
+            let icon = icon.canonicalize().unwrap_or(icon.clone());
+            match config
+                .get_icon_store()
+                .and_then(|icon_store| to_steam_icon(&icon, &icon_store))
+            {
+                Some(value) => value,
+                None => {
+                    println!("Could not convert the icon {}", icon.display());
+                    return false;
+                }
+            }
+        }
+        None => String::new(),
+    };
+    let app_name = add_shortcut_config.name.clone().unwrap_or_else(|| {
+        exe.file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string()
+    });
+
+    let exe = quote_path(&exe.to_string_lossy());
+    let appid = config.app_id_algorithm.generate_app_id(
+        &exe,
+        &app_name,
+        &add_shortcut_config.launch_options,
+    );
+    let mut shortcut = ShortcutEntry {
+        appid,
+        app_name,
+        exe,
+        start_dir: quote_path(&start_dir.to_string_lossy()),
+        icon,
+        launch_options: add_shortcut_config.launch_options.clone(),
+        is_hidden: add_shortcut_config.hidden as u32,
+        allow_desktop_config: !add_shortcut_config.no_desktop_config as u32,
+        allow_overlay: !add_shortcut_config.no_overlay as u32,
+        open_vr: add_shortcut_config.open_vr as u32,
+        tags: add_shortcut_config.tags.clone(),
+        ..Default::default()
+    };
+
+    if add_shortcut_config.artwork == Artwork::Generate && shortcut.icon.is_empty() {
+        println!("Generating artwork needs an --icon");
+        return false;
+    }
+
+    config.use_existing_appids(std::slice::from_mut(&mut shortcut));
+    if !config.insert_shortcut(shortcut.clone()) {
+        println!("Failed to insert shortcut into the shortcuts file.");
+        return false;
+    }
+
+    match add_shortcut_config.artwork {
+        Artwork::None => {}
+        Artwork::Generate => match config.get_grid_folder() {
+            Some(grid_path) => {
+                if let Err(err) = create_grid_for_shortcut(&shortcut, &grid_path) {
+                    println!("Failed to generate the artwork - {err}");
+                    return false;
+                }
+            }
+            None => println!("Could not find the grid folder"),
+        },
+        Artwork::Download => {
+            let downloaded = try_download_grid_files(
+                &shortcut.app_name,
+                config,
+                shortcut.appid,
+                add_shortcut_config.steam_id,
+            );
+            if let Some((_, _, portrait, _)) = downloaded
+                && shortcut.icon.is_empty()
+            {
+                shortcut.icon = portrait.to_string_lossy().to_string();
+                if !config.insert_shortcut(shortcut) {
+                    println!("Failed to set the downloaded artwork as the icon.");
+                    return false;
+                }
+            }
+        }
+    }
+    true
+}
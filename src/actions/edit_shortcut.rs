@@ -0,0 +1,122 @@
+use crate::config::{AppIdAlgorithm, Config, EditShortcut};
+use crate::shortcuts::Shortcuts;
+use crate::shortcuts::selector::ShortcutSelector;
+use crate::shortcuts::shortcut::ShortcutEntry;
+use crate::utils::move_grid_files;
+use anyhow::anyhow;
+
+pub(crate) fn edit_shortcut(config: &Config, edit_shortcut_config: &EditShortcut) -> bool {
+    let selector = match ShortcutSelector::try_from(&edit_shortcut_config.select) {
+        Ok(value) => value,
+        Err(err) => {
+            println!("Invalid selection - {err}");
+            return false;
+        }
+    };
+    let Some(shortcuts_file_path) = config.find_steam_shortcuts_file() else {
+        println!("Failed to find the shortcuts");
+        return false;
+    };
+    let Some(mut shortcuts) = Shortcuts::from_path(&shortcuts_file_path) else {
+        println!("Could not read the shortcuts file");
+        return false;
+    };
+
+    let mut edited: Vec<(ShortcutEntry, usize)> = Vec::new();
+    for (index, shortcut) in shortcuts.shortcuts.iter_mut().enumerate() {
+        if !selector.matches(shortcut) {
+            continue;
+        }
+        let before = shortcut.clone();
+        if let Err(err) = apply_edits(shortcut, edit_shortcut_config, config.app_id_algorithm) {
+            println!(
+                "Failed to edit {} ({}) - {err}",
+                before.app_name, before.appid
+            );
+            return false;
+        }
+        edited.push((before, index));
+    }
+    if edited.is_empty() {
+        println!("No shortcut matches");
+        return true;
+    }
+
+    for (before, index) in &edited {
+        let appid = shortcuts.shortcuts[*index].appid;
+        if appid != before.appid
+            && let Some(other) = shortcuts
+                .shortcuts
+                .iter()
+                .enumerate()
+                .find(|(i, s)| i != index && s.appid == appid)
+        {
+            println!(
+                "{} would get the appid {appid} of {}, nothing was changed",
+                before.app_name, other.1.app_name
+            );
+            return false;
+        }
+    }
+
+    let grid_folder = config.get_grid_folder().unwrap_or_default();
+    let roots = config.get_root_mapping();
+    let mut all_moved = Vec::new();
+    for (before, index) in &edited {
+        let shortcut = &mut shortcuts.shortcuts[*index];
+        if shortcut.appid != before.appid {
+            match move_grid_files(before, shortcut, &grid_folder) {
+                Ok(moved) => {
+                    for (from, to) in moved {
+                        if roots.to_local(&shortcut.icon) == from.to_string_lossy() {
+                            shortcut.icon = roots.to_target(&to.to_string_lossy());
+                        }
+                        println!("  Moved {} to {}", from.display(), to.display());
+                        all_moved.push((from, to));
+                    }
+                }
+                Err(err) => println!("Failed to move the artwork of {} - {err}", before.app_name),
+            }
+        }
+        println!("Edited {} ({})", shortcut.app_name, shortcut.appid);
+    }
+
+    if let Err(err) = shortcuts.save_to(&shortcuts_file_path) {
+        println!("Failed to save the shortcuts file - {err}");
+        for (from, to) in all_moved.iter().rev() {
+            std::fs::rename(to, from).ok();
+        }
+        return false;
+    }
+    true
+}
+
+fn apply_edits(
+    shortcut: &mut ShortcutEntry,
+    edit_shortcut_config: &EditShortcut,
+    app_id_algorithm: AppIdAlgorithm,
+) -> anyhow::Result<()> {
+    for assignment in &edit_shortcut_config.set {
+        let (key, value) = assignment
+            .split_once('=')
+            .ok_or(anyhow!("{assignment} is not of the form KEY=VALUE"))?;
+        shortcut.set_field(key.trim(), value)?;
+    }
+    for key in &edit_shortcut_config.unset {
+        shortcut.unset_field(key)?;
+    }
+    for tag in &edit_shortcut_config.add_tag {
+        if !shortcut.tags.contains(tag) {
+            shortcut.tags.push(tag.clone());
+        }
+    }
+    shortcut
+        .tags
+        .retain(|tag| !edit_shortcut_config.remove_tag.contains(tag));
+
+    if edit_shortcut_config.update_appid {
+        let (exe, legacy_name) = shortcut.legacy_app_id_inputs();
+        shortcut.appid = app_id_algorithm.generate_app_id(exe, &shortcut.app_name, &legacy_name);
+    }
+    Ok(())
+}
@@ -1,46 +1,130 @@
-use crate::config::{AddFlatpak, Config};
-use crate::flatpak::create_flatpak_shortcut;
+use crate::config::{AddFlatpak, Config, SelectActions};
+use crate::desktop_file::{DesktopAction, DesktopFile};
+use crate::flatpak::{create_flatpak_shortcut, find_flatpak_desktop_file};
 use crate::shortcuts::Shortcuts;
+use crate::shortcuts::shortcut::ShortcutEntry;
 use crate::utils::create_grid_for_shortcut;
 
-pub(crate) fn fix_flatpak(config: &Config, force: bool) {
+pub(crate) fn fix_flatpak(config: &Config, force: bool) -> bool {
+    let Some(flatpak_paths) = config.get_flatpak_paths() else {
+        println!("Could not find the home folder");
+        return false;
+    };
     let Some(shortcuts_file_path) = config.find_steam_shortcuts_file() else {
         println!("Failed to find the shortcuts");
-        return;
+        return false;
     };
     let Ok(bytes) = std::fs::read(&shortcuts_file_path) else {
         println!("Could not read the shortcuts file");
-        return;
+        return false;
     };
-    let Ok(mut shortcuts) = Shortcuts::try_from_bytes(&bytes) else {
-        println!(
-            "Failed to parse the shortcuts file at {}",
-            shortcuts_file_path.display()
-        );
-        return;
+    let mut shortcuts = match Shortcuts::try_from_bytes(&bytes) {
+        Ok(value) => value,
+        Err(err) => {
+            println!(
+                "Failed to parse the shortcuts file at {} - {err}",
+                shortcuts_file_path.display()
+            );
+            return false;
+        }
+    };
+    if shortcuts.fix_flatpaks(
+        &shortcuts_file_path.parent().unwrap().join("grid"),
+        force,
+        &flatpak_paths,
+        &config.get_data_dirs(),
+        &config.get_root_mapping(),
+    ) && let Err(err) = shortcuts.save_to(&shortcuts_file_path)
+    {
+        println!("Failed to save the shortcuts file - {err}");
+        return false;
+    }
+    true
+}
+
+pub(crate) fn add_flatpak(config: &Config, add_flatpak_config: &AddFlatpak) -> bool {
+    let flatpak_id = &add_flatpak_config.flatpak_id;
+    let Some(flatpak_paths) = config.get_flatpak_paths() else {
+        println!("Could not find the home folder");
+        return false;
+    };
+    let (desktop_file_path, desktop_file) =
+        match find_flatpak_desktop_file(flatpak_id, &flatpak_paths, config.get_locale().as_deref())
+        {
+            Ok(value) => value,
+            Err(err) => {
+                println!("Failed to create a shortcut for flatpak {flatpak_id} - Err {err:?}");
+                return false;
+            }
+        };
+    let Some(actions) = select_actions(&desktop_file, &add_flatpak_config.actions) else {
+        return false;
     };
-    if shortcuts.fix_flatpaks(&shortcuts_file_path.parent().unwrap().join("grid"), force) {
-        shortcuts.save_to(&shortcuts_file_path);
+
+    let data_dirs = config.get_data_dirs();
+    let mut shortcuts = Vec::new();
+    for action in std::iter::once(None).chain(actions.into_iter().map(Some)) {
+        match create_flatpak_shortcut(
+            flatpak_id,
+            &desktop_file_path,
+            &desktop_file,
+            action,
+            &flatpak_paths,
+            &data_dirs,
+            config.app_id_algorithm,
+        ) {
+            Ok(value) => shortcuts.push(value),
+            Err(err) => {
+                println!("Failed to create a shortcut for flatpak {flatpak_id} - Err {err:?}");
+                return false;
+            }
+        }
     }
+    insert_with_artwork(config, shortcuts)
 }
 
-pub(crate) fn add_flatpak(config: &Config, add_flatpak_config: &AddFlatpak) {
-    let shortcut = match create_flatpak_shortcut(&add_flatpak_config.flatpak_id) {
+/// The selected actions. Without a selection the available actions are pointed out.
+pub(crate) fn select_actions<'a>(
+    desktop_file: &'a DesktopFile,
+    select_actions_config: &SelectActions,
+) -> Option<Vec<&'a DesktopAction>> {
+    let actions = match desktop_file.select_actions(
+        &select_actions_config.actions,
+        select_actions_config.all_actions,
+    ) {
         Ok(value) => value,
         Err(err) => {
-            println!(
-                "Failed to create a shortcut for flatpak {} - Err {err:?}",
-                add_flatpak_config.flatpak_id
-            );
-            return;
+            println!("{err}");
+            return None;
         }
     };
+    if actions.is_empty() && !desktop_file.actions.is_empty() {
+        println!(
+            "{} also has the actions {}, add them with --action ID or --all-actions",
+            desktop_file.name,
+            desktop_file.list_actions()
+        );
+    }
+    Some(actions)
+}
 
-    if let Some(grid_path) = config.get_grid_folder() {
-        create_grid_for_shortcut(&shortcut, &grid_path);
+/// Inserts the shortcuts and generates their artwork once they are saved.
+pub(crate) fn insert_with_artwork(config: &Config, mut shortcuts: Vec<ShortcutEntry>) -> bool {
+    config.use_existing_appids(&mut shortcuts);
+    if !config.insert_shortcuts(shortcuts.clone()) {
+        println!("Failed to insert shortcut into the shortcuts file.");
+        return false;
     }
 
-    if !config.insert_shortcut(shortcut) {
-        println!("Failed to insert shortcut into the shortcuts file.")
+    let grid_path = config.get_grid_folder();
+    for shortcut in &shortcuts {
+        if shortcut.icon.is_empty() {
+            println!("No icon found, {} has no artwork", shortcut.app_name);
+        } else if let Some(grid_path) = &grid_path
+            && let Err(err) = create_grid_for_shortcut(shortcut, grid_path)
+        {
+            println!("No artwork for {} - {err}", shortcut.app_name);
+        }
     }
+    true
 }
@@ -0,0 +1,59 @@
+use crate::config::{Config, ListUsers};
+use crate::steam::steam_user::SteamUser;
+
+pub(crate) fn list_users(config: &Config, list_users_config: &ListUsers) {
+    let Some(steam_root) = config.get_steam_root() else {
+        println!("Could not find the Steam root");
+        return;
+    };
+
+    let users = match SteamUser::list(&steam_root) {
+        Ok(value) => value,
+        Err(err) => {
+            println!(
+                "Failed to list the users in {} - {err}",
+                steam_root.display()
+            );
+            return;
+        }
+    };
+
+    if list_users_config.json {
+        println!("{}", serde_json::to_string_pretty(&users).unwrap());
+        return;
+    }
+
+    println!(
+        "{:<12} {:<18} {:<16} {:<20} {:<20} {:<19} {:>9} {:>11}",
+        "USER ID",
+        "STEAM ID 64",
+        "STEAM ID 3",
+        "ACCOUNT NAME",
+        "PERSONA NAME",
+        "LAST LOGIN",
+        "SHORTCUTS",
+        "GRID IMAGES"
+    );
+    for user in users {
+        let or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+        println!(
+            "{:<12} {:<18} {:<16} {:<20} {:<20} {:<19} {:>9} {:>11}{}",
+            user.user_id,
+            or_dash(user.steam_id_64.map(|i| i.to_string())),
+            or_dash(user.steam_id_3),
+            or_dash(user.account_name),
+            or_dash(user.persona_name),
+            or_dash(
+                user.last_login
+                    .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+            ),
+            or_dash(user.shortcut_count.map(|i| i.to_string())),
+            user.grid_image_count,
+            if user.most_recent {
+                "  (most recent)"
+            } else {
+                ""
+            }
+        );
+    }
+}
@@ -1,14 +1,14 @@
 use crate::config::{AddMinusGamesGameToSteam, Config};
 use crate::shortcuts::shortcut::ShortcutEntry;
-use crate::utils::{generate_app_id, try_download_grid_files};
+use crate::utils::try_download_grid_files;
 
 pub(crate) fn add_minus_games_game_to_steam(
     config: &Config,
     sub_config: &AddMinusGamesGameToSteam,
-) {
+) -> bool {
     if !sub_config.game_path.is_dir() {
         println!("Game path is not a directory");
-        return;
+        return false;
     }
 
     let game_name = sub_config
@@ -22,8 +22,10 @@ pub(crate) fn add_minus_games_game_to_steam(
     let shortcut =
         create_minus_games_shortcut_from_game_name(game_name, config, sub_config.steam_id);
     if !config.insert_shortcut(shortcut) {
-        println!("Failed to add shortcut.")
+        println!("Failed to add shortcut.");
+        return false;
     }
+    true
 }
 
 fn create_minus_games_shortcut_from_game_name(
@@ -36,21 +38,16 @@ fn create_minus_games_shortcut_from_game_name(
     let launch_options = format!(
         "run --branch=stable --arch=x86_64 --command=/app/bin/minus_games_gui io.github.accessory.minus_games_gui --mode Cli run-game-synced \"{game_name}\""
     );
-    let appid = generate_app_id(EXE, &launch_options);
+    let appid = config
+        .app_id_algorithm
+        .generate_app_id(EXE, game_name, &launch_options);
 
-    let icon =
-        if let Some((_, _, p, _)) = try_download_grid_files(game_name, config, appid, steam_id) {
-            p.to_str().unwrap().to_string()
-        } else {
-            "".to_string()
-        };
-
-    ShortcutEntry {
+    let mut rtn = ShortcutEntry {
         appid,
         app_name: game_name.to_string(),
-        exe: EXE.to_string(),
-        start_dir: "/usr/bin".to_string(),
-        icon,
+        exe: config.app_id_algorithm.format_path(EXE),
+        start_dir: config.app_id_algorithm.format_path("/usr/bin"),
+        icon: "".to_string(),
         shortcut_path: "".to_string(),
         launch_options,
         is_hidden: 0,
@@ -64,5 +61,14 @@ fn create_minus_games_shortcut_from_game_name(
         flatpak_app_id: "".to_string(),
         sort_as: "".to_string(),
         tags: vec![],
+        extra_fields: vec![],
+        key_order: vec![],
+        flatpak_action_id: "".to_string(),
+    };
+    config.use_existing_appids(std::slice::from_mut(&mut rtn));
+
+    if let Some((_, _, p, _)) = try_download_grid_files(game_name, config, rtn.appid, steam_id) {
+        rtn.icon = p.to_str().unwrap().to_string();
     }
+    rtn
 }
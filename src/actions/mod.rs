@@ -1,3 +1,9 @@
+pub(crate) mod add_desktop_file;
+pub(crate) mod add_shortcut;
+pub(crate) mod edit_shortcut;
 pub(crate) mod flatpak;
+pub(crate) mod list_users;
 pub(crate) mod minus_games;
 pub(crate) mod print_shortcuts;
+pub(crate) mod remove_shortcut;
+pub(crate) mod snapshots;
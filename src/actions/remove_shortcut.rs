@@ -0,0 +1,127 @@
+use crate::config::{Config, RemoveShortcut};
+use crate::shortcuts::Shortcuts;
+use crate::shortcuts::selector::ShortcutSelector;
+use crate::utils::{confirm, find_grid_files};
+use std::path::{Path, PathBuf};
+
+pub(crate) fn remove_shortcut(config: &Config, remove_shortcut_config: &RemoveShortcut) -> bool {
+    let selector = match ShortcutSelector::try_from(&remove_shortcut_config.select) {
+        Ok(value) => value,
+        Err(err) => {
+            println!("Invalid selection - {err}");
+            return false;
+        }
+    };
+    let Some(shortcuts_file_path) = config.find_steam_shortcuts_file() else {
+        println!("Failed to find the shortcuts");
+        return false;
+    };
+    let Some(mut shortcuts) = Shortcuts::from_path(&shortcuts_file_path) else {
+        println!("Could not read the shortcuts file");
+        return false;
+    };
+
+    let removed = shortcuts.remove_shortcuts(&selector);
+    if removed.is_empty() {
+        println!("No shortcut matches");
+        return true;
+    }
+
+    let grid_folder = config.get_grid_folder().unwrap_or_default();
+    let icon_store = config.get_icon_store();
+    let roots = config.get_root_mapping();
+    // The icon store is shared by all users, their shortcuts keep the icons in use.
+    let other_users_shortcuts = find_other_users_shortcuts(config, &shortcuts_file_path);
+    let mut files: Vec<PathBuf> = Vec::new();
+    for shortcut in &removed {
+        println!(
+            "{} ({}) - {}",
+            shortcut.app_name, shortcut.appid, shortcut.exe
+        );
+        files.extend(find_grid_files(shortcut, &grid_folder));
+        let icon = PathBuf::from(roots.to_local(&shortcut.icon));
+        if let Some(icon_store) = &icon_store
+            && let Some(other_users_shortcuts) = &other_users_shortcuts
+            && is_unused_cached_icon(
+                &shortcut.icon,
+                &icon,
+                icon_store,
+                std::iter::once(&shortcuts).chain(other_users_shortcuts),
+            )
+            && !files.contains(&icon)
+        {
+            files.push(icon);
+        }
+    }
+    for file in &files {
+        println!("  {}", file.display());
+    }
+
+    if !remove_shortcut_config.yes
+        && !confirm(&format!(
+            "Remove {} shortcut(s) and {} file(s)?",
+            removed.len(),
+            files.len()
+        ))
+    {
+        println!("Nothing was removed");
+        return true;
+    }
+
+    if let Err(err) = shortcuts.save_to(&shortcuts_file_path) {
+        println!("Failed to save the shortcuts file - {err}");
+        return false;
+    }
+    for file in &files {
+        if let Err(err) = std::fs::remove_file(file) {
+            println!("Failed to remove {} - {err}", file.display());
+        }
+    }
+    println!("Removed {} shortcut(s)", removed.len());
+    true
+}
+
+/// Icons converted into the icon store can go once no remaining shortcut of any user points
+/// to them. `local_icon` is where the stored `icon` is on this system.
+fn is_unused_cached_icon<'a>(
+    icon: &str,
+    local_icon: &Path,
+    icon_store: &Path,
+    mut remaining: impl Iterator<Item = &'a Shortcuts>,
+) -> bool {
+    !icon.is_empty()
+        && local_icon.starts_with(icon_store)
+        && local_icon.is_file()
+        && !remaining.any(|shortcuts| shortcuts.shortcuts.iter().any(|s| s.icon == icon))
+}
+
+/// The shortcuts of every other user below `userdata`. `None` if the shortcuts of one of
+/// them can not be read, as the icons they use are unknown then.
+fn find_other_users_shortcuts(
+    config: &Config,
+    shortcuts_file_path: &Path,
+) -> Option<Vec<Shortcuts>> {
+    let Some(steam_root) = config.get_steam_root() else {
+        return Some(Vec::new());
+    };
+    let Ok(user_folders) = steam_root.join("userdata").read_dir() else {
+        return Some(Vec::new());
+    };
+    let own_shortcuts_file_path = shortcuts_file_path.canonicalize().ok();
+
+    let mut rtn = Vec::new();
+    for user_folder in user_folders.flatten() {
+        let path = user_folder.path().join("config/shortcuts.vdf");
+        if !path.is_file() || path.canonicalize().ok() == own_shortcuts_file_path {
+            continue;
+        }
+        match Shortcuts::from_path(&path) {
+            Some(shortcuts) => rtn.push(shortcuts),
+            None => {
+                println!("Could not read {}, cached icons are kept", path.display());
+                return None;
+            }
+        }
+    }
+    Some(rtn)
+}
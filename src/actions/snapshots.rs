@@ -0,0 +1,121 @@
+use crate::config::{Backup, Config, Restore};
+use crate::snapshots::{
+    Snapshot, create_snapshot, find_snapshot, get_snapshot_folder, list_snapshots, restore_snapshot,
+};
+use std::path::{Path, PathBuf};
+
+pub(crate) fn backup(config: &Config, backup_config: &Backup) {
+    let Some(snapshot_folder) = find_snapshot_folder(config, &backup_config.snapshot_folder) else {
+        return;
+    };
+
+    if backup_config.list {
+        let shortcuts_file_path = config.find_steam_shortcuts_file();
+        print_snapshots(&snapshot_folder, shortcuts_file_path.as_deref());
+        return;
+    }
+
+    let Some((shortcuts_file_path, grid_folder)) = find_shortcuts_and_grid(config) else {
+        return;
+    };
+
+    match create_snapshot(&shortcuts_file_path, &grid_folder, &snapshot_folder) {
+        Ok(snapshot) => println!("Created snapshot {}", snapshot.path.display()),
+        Err(err) => println!("Failed to create the snapshot - {err}"),
+    }
+}
+
+pub(crate) fn restore(config: &Config, restore_config: &Restore) {
+    let Some(snapshot_folder) = find_snapshot_folder(config, &restore_config.snapshot_folder)
+    else {
+        return;
+    };
+
+    // Only the snapshots of the current user are restored, or of the user the snapshot was
+    // taken of if the shortcuts file is gone.
+    let target = find_shortcuts_and_grid(config);
+    let target_shortcuts_file = target.as_ref().map(|(path, _)| path.as_path());
+    let Some(snapshot_name) = &restore_config.snapshot else {
+        print_snapshots(&snapshot_folder, target_shortcuts_file);
+        return;
+    };
+
+    let snapshot = match find_snapshot(&snapshot_folder, snapshot_name, target_shortcuts_file) {
+        Ok(value) => value,
+        Err(err) => {
+            println!("{err}");
+            return;
+        }
+    };
+
+    let (shortcuts_file_path, grid_folder) = target.unwrap_or((
+        snapshot.manifest.shortcuts_file.clone(),
+        snapshot.manifest.grid_folder.clone(),
+    ));
+
+    match create_snapshot(&shortcuts_file_path, &grid_folder, &snapshot_folder) {
+        Ok(current) => println!("Saved the current state as {}", current.name),
+        Err(err) => {
+            println!("Failed to save the current state, nothing was restored - {err}");
+            return;
+        }
+    }
+
+    match restore_snapshot(&snapshot, &shortcuts_file_path, &grid_folder) {
+        Ok(_) => println!(
+            "Restored snapshot {} to {}",
+            snapshot.name,
+            shortcuts_file_path.display()
+        ),
+        Err(err) => println!("Failed to restore snapshot {} - {err}", snapshot.name),
+    }
+}
+
+fn find_snapshot_folder(config: &Config, snapshot_folder: &Option<PathBuf>) -> Option<PathBuf> {
+    let rtn = snapshot_folder.clone().or_else(|| {
+        config
+            .get_data_dir()
+            .map(|data_dir| get_snapshot_folder(&data_dir))
+    });
+    if rtn.is_none() {
+        println!("Could not find the snapshot folder");
+    }
+    rtn
+}
+
+fn find_shortcuts_and_grid(config: &Config) -> Option<(PathBuf, PathBuf)> {
+    let Some(shortcuts_file_path) = config.find_steam_shortcuts_file() else {
+        println!("Failed to find the shortcuts");
+        return None;
+    };
+    let Some(grid_folder) = config.get_grid_folder() else {
+        println!("Could not find the grid folder");
+        return None;
+    };
+    Some((shortcuts_file_path, grid_folder))
+}
+
+fn print_snapshots(snapshot_folder: &Path, shortcuts_file_path: Option<&Path>) {
+    let snapshots: Vec<Snapshot> = match list_snapshots(snapshot_folder, shortcuts_file_path) {
+        Ok(value) => value,
+        Err(err) => {
+            println!("Failed to list the snapshots - {err}");
+            return;
+        }
+    };
+
+    if snapshots.is_empty() {
+        println!("No snapshots in {}", snapshot_folder.display());
+        return;
+    }
+
+    for snapshot in snapshots {
+        println!(
+            "{}  {}  {} shortcuts  {}",
+            snapshot.name,
+            snapshot.manifest.created.format("%Y-%m-%d %H:%M:%S"),
+            snapshot.manifest.shortcut_count,
+            snapshot.manifest.shortcuts_file.display()
+        );
+    }
+}
@@ -1,9 +1,16 @@
+use crate::flatpak::FlatpakPaths;
 use crate::shortcuts::Shortcuts;
 use crate::shortcuts::shortcut::ShortcutEntry;
-use crate::steam::get_user_id_fast;
-use clap::{Parser, Subcommand};
+use crate::steam::running::{find_running_steam, wait_for_steam_exit};
+use crate::steam::{find_steam_roots, find_user_id, get_steam_pid_files, is_local_steam_root};
+use crate::utils::{generate_app_id, generate_game_id, generate_steam_app_id, quote_path};
+use anyhow::anyhow;
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Where the system Flatpak installation is on the system Steam runs on.
+pub(crate) const FLATPAK_SYSTEM_ROOT: &str = "/var/lib/flatpak";
 
 #[derive(Parser, Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct AddMinusGamesGameToSteam {
@@ -18,6 +25,69 @@ pub(crate) struct AddMinusGamesGameToSteam {
 #[derive(Parser, Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct AddFlatpak {
     pub flatpak_id: String,
+    #[command(flatten)]
+    pub actions: SelectActions,
+}
+
+/// Desktop actions, like "New Private Window", that get a shortcut of their own.
+#[derive(Args, Debug, Serialize, Deserialize, Clone, Default)]
+pub(crate) struct SelectActions {
+    /// Id of a desktop action to add as well, e.g. `new-private-window`
+    #[arg(long = "action", value_name = "ID")]
+    pub actions: Vec<String>,
+    /// Add every desktop action as well
+    #[arg(long, env, default_value = "false")]
+    pub all_actions: bool,
+}
+
+#[derive(ValueEnum, Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum Artwork {
+    #[default]
+    None,
+    /// Generate the grid images from the icon
+    Generate,
+    /// Download the grid images of the matching Steam game
+    Download,
+}
+
+#[derive(Parser, Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct AddShortcut {
+    /// Path of the executable or its name in PATH
+    pub exe: PathBuf,
+    /// Name shown in Steam. The file name of the executable if omitted
+    #[arg(long)]
+    pub name: Option<String>,
+    /// Working directory. The folder of the executable if omitted
+    #[arg(long)]
+    pub start_dir: Option<PathBuf>,
+    #[arg(long, default_value = "")]
+    pub launch_options: String,
+    #[arg(long)]
+    pub icon: Option<PathBuf>,
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+    #[arg(long, default_value = "false")]
+    pub hidden: bool,
+    #[arg(long, default_value = "false")]
+    pub no_overlay: bool,
+    #[arg(long, default_value = "false")]
+    pub no_desktop_config: bool,
+    #[arg(long, default_value = "false")]
+    pub open_vr: bool,
+    #[arg(long, value_enum, default_value_t = Artwork::None)]
+    pub artwork: Artwork,
+    /// Steam game whose artwork is downloaded. Searched by name if omitted
+    #[arg(long)]
+    pub steam_id: Option<u32>,
+}
+
+#[derive(Parser, Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct AddDesktopFile {
+    /// Path of the desktop file or its id, e.g. `org.gnome.Calculator`, or the window class
+    /// or a keyword of the application
+    pub desktop_file: String,
+    #[command(flatten)]
+    pub actions: SelectActions,
 }
 
 #[derive(Parser, Debug, Serialize, Deserialize, Clone)]
@@ -26,17 +96,157 @@ pub(crate) struct FixFlatpak {
     pub force: bool,
 }
 
+#[derive(Parser, Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct Backup {
+    /// List the existing snapshots instead of creating a new one
+    #[arg(long, default_value = "false")]
+    pub list: bool,
+    #[arg(long, env)]
+    pub snapshot_folder: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct Restore {
+    /// Name of the snapshot or `latest`. Lists the snapshots if omitted
+    pub snapshot: Option<String>,
+    #[arg(long, env)]
+    pub snapshot_folder: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct ListUsers {
+    /// Print the users as JSON instead of a table
+    #[arg(long, default_value = "false")]
+    pub json: bool,
+}
+
+/// Selects shortcuts by every criterion that is given.
+#[derive(Args, Debug, Serialize, Deserialize, Clone)]
+#[group(required = true, multiple = true)]
+pub(crate) struct SelectShortcuts {
+    #[arg(long)]
+    pub appid: Option<u32>,
+    /// Exact name of the shortcut
+    #[arg(long)]
+    pub name: Option<String>,
+    /// Glob pattern matched against the name and the exe
+    #[arg(long)]
+    pub glob: Option<String>,
+    /// Regular expression matched against the name and the exe
+    #[arg(long)]
+    pub regex: Option<String>,
+    #[arg(long)]
+    pub flatpak_id: Option<String>,
+}
+
+#[derive(Parser, Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct RemoveShortcut {
+    #[command(flatten)]
+    pub select: SelectShortcuts,
+    /// Remove the shortcuts without asking
+    #[arg(long, short, default_value = "false")]
+    pub yes: bool,
+}
+
+#[derive(Parser, Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct EditShortcut {
+    #[command(flatten)]
+    pub select: SelectShortcuts,
+    /// Sets a field by its VDF key or field name, e.g. `LaunchOptions=-fullscreen` or `is_hidden=1`
+    #[arg(long, value_name = "KEY=VALUE")]
+    pub set: Vec<String>,
+    /// Removes a key that this tool does not know
+    #[arg(long, value_name = "KEY")]
+    pub unset: Vec<String>,
+    #[arg(long, value_name = "TAG")]
+    pub add_tag: Vec<String>,
+    #[arg(long, value_name = "TAG")]
+    pub remove_tag: Vec<String>,
+    /// Generate the appid again from the exe and name and move the artwork to it
+    #[arg(long, default_value = "false")]
+    pub update_appid: bool,
+}
+
 #[derive(Debug, Subcommand, Serialize, Deserialize, strum::Display, Clone, Default)]
 pub(crate) enum Actions {
     #[default]
     PrintShortcuts,
     PrintOnlyShortcuts,
+    ListUsers(ListUsers),
     #[cfg(not(target_family = "windows"))]
     AddMinusGamesGameToSteam(AddMinusGamesGameToSteam),
     #[cfg(not(target_family = "windows"))]
     FixFlatpak(FixFlatpak),
     #[cfg(not(target_family = "windows"))]
     AddFlatpak(AddFlatpak),
+    #[cfg(not(target_family = "windows"))]
+    AddShortcut(AddShortcut),
+    #[cfg(not(target_family = "windows"))]
+    AddDesktopFile(AddDesktopFile),
+    EditShortcut(EditShortcut),
+    RemoveShortcut(RemoveShortcut),
+    Backup(Backup),
+    Restore(Restore),
+}
+
+impl Actions {
+    /// Actions that write the shortcuts file, which Steam overwrites when it exits.
+    pub(crate) fn is_mutating(&self) -> bool {
+        match self {
+            Actions::PrintShortcuts
+            | Actions::PrintOnlyShortcuts
+            | Actions::ListUsers(_)
+            | Actions::Backup(_) => false,
+            #[cfg(not(target_family = "windows"))]
+            Actions::AddMinusGamesGameToSteam(_)
+            | Actions::FixFlatpak(_)
+            | Actions::AddFlatpak(_)
+            | Actions::AddShortcut(_)
+            | Actions::AddDesktopFile(_) => true,
+            Actions::EditShortcut(_) | Actions::RemoveShortcut(_) | Actions::Restore(_) => true,
+        }
+    }
+
+    /// Actions that can be applied to every user with `--all-users`.
+    pub(crate) fn supports_all_users(&self) -> bool {
+        match self {
+            #[cfg(not(target_family = "windows"))]
+            Actions::AddMinusGamesGameToSteam(_)
+            | Actions::FixFlatpak(_)
+            | Actions::AddFlatpak(_)
+            | Actions::AddShortcut(_)
+            | Actions::AddDesktopFile(_) => true,
+            Actions::EditShortcut(_) | Actions::RemoveShortcut(_) => true,
+            _ => false,
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum AppIdAlgorithm {
+    /// The quoted exe and the app name, like Steam's "Add a Non-Steam Game"
+    #[default]
+    Steam,
+    /// The unquoted exe and the launch options or Flatpak id, like earlier versions of this tool
+    Legacy,
+}
+
+impl AppIdAlgorithm {
+    /// `legacy_name` is what earlier versions hashed instead of the app name.
+    pub(crate) fn generate_app_id(self, exe: &str, app_name: &str, legacy_name: &str) -> u32 {
+        match self {
+            AppIdAlgorithm::Steam => generate_steam_app_id(exe, app_name),
+            AppIdAlgorithm::Legacy => generate_app_id(exe, legacy_name),
+        }
+    }
+
+    /// The exe or start dir as it is stored in the shortcut.
+    pub(crate) fn format_path(self, path: &str) -> String {
+        match self {
+            AppIdAlgorithm::Steam => quote_path(path),
+            AppIdAlgorithm::Legacy => path.to_string(),
+        }
+    }
 }
 
 #[derive(Parser, Debug, Serialize, Deserialize, Clone)]
@@ -44,10 +254,44 @@ pub(crate) enum Actions {
 pub struct Config {
     #[command(subcommand)]
     pub action: Option<Actions>,
+    /// Steam installation folder, detected if omitted
+    #[arg(long, env)]
+    pub steam_root: Option<PathBuf>,
+    /// Account id, SteamID64, Steam2 or Steam3 id, account name or persona name
     #[arg(long, env)]
     pub steam_user_id: Option<String>,
     #[arg(long, env)]
     pub steam_shortcuts_file: Option<PathBuf>,
+    /// Home folder that Steam, user Flatpaks and the icon store are looked up in
+    #[arg(long, env)]
+    pub home_dir: Option<PathBuf>,
+    /// Path of the `--home-dir` on the system Steam runs on, e.g. `/home/deck`. Paths below
+    /// the home folder are stored with it
+    #[arg(long, env)]
+    pub target_home_dir: Option<PathBuf>,
+    /// System Flatpak installation with the exported desktop files and icons. Paths below it
+    /// are stored below /var/lib/flatpak
+    #[arg(long, env, default_value = FLATPAK_SYSTEM_ROOT)]
+    pub flatpak_root: PathBuf,
+    /// How the appid of new shortcuts is generated
+    #[arg(long, env, value_enum, default_value_t = AppIdAlgorithm::Steam)]
+    pub app_id_algorithm: AppIdAlgorithm,
+    /// Locale of the names taken from desktop files, e.g. `de_DE`. Defaults to `LC_ALL`,
+    /// `LC_MESSAGES` or `LANG`
+    #[arg(long, env)]
+    pub locale: Option<String>,
+    /// Icon theme that icons are looked up in before the themes it inherits from and hicolor
+    #[arg(long, env)]
+    pub icon_theme: Option<String>,
+    /// Apply the action to every Steam user instead of a single one
+    #[arg(long, env, default_value = "false")]
+    pub all_users: bool,
+    /// Wait until Steam is closed before changing the shortcuts
+    #[arg(long, env, default_value = "false")]
+    pub wait_for_steam_exit: bool,
+    /// Change the shortcuts even if Steam is running
+    #[arg(long, env, default_value = "false")]
+    pub force_while_steam_running: bool,
 }
 
 impl Config {
@@ -55,7 +299,62 @@ impl Config {
         self.action.as_ref().unwrap_or(&Actions::PrintShortcuts)
     }
 
+    /// Returns `false` if Steam is running and the action should not change the shortcuts.
+    /// Steam roots that are not part of the home folder of this system, like a mounted
+    /// Steam Deck or a copy, are not checked because the processes of this system have
+    /// nothing to do with them.
+    pub(crate) fn ensure_steam_is_not_running(&self) -> bool {
+        let (Some(home_dir), Some(steam_root)) = (dirs::home_dir(), self.get_steam_root()) else {
+            return true;
+        };
+        if !is_local_steam_root(&home_dir, &steam_root) {
+            return true;
+        }
+        let pid_files = get_steam_pid_files(&home_dir, &steam_root);
+        let Some(pid) = find_running_steam(&pid_files) else {
+            return true;
+        };
+
+        if self.force_while_steam_running {
+            println!("Steam is running (pid {pid}), it might overwrite the changes when it exits");
+            return true;
+        }
+
+        if self.wait_for_steam_exit {
+            println!("Waiting for Steam (pid {pid}) to exit");
+            wait_for_steam_exit(&pid_files);
+            return true;
+        }
+
+        println!(
+            "Steam is running (pid {pid}) and overwrites the shortcuts when it exits. Close Steam or use --wait-for-steam-exit or --force-while-steam-running"
+        );
+        false
+    }
+
+    /// Gives the shortcuts the appids of the shortcuts they replace when they are inserted,
+    /// so that their artwork is created for the appid they keep.
+    pub(crate) fn use_existing_appids(&self, shortcut_entries: &mut [ShortcutEntry]) {
+        let Some(shortcuts) = self
+            .find_steam_shortcuts_file()
+            .and_then(|path| Shortcuts::from_path(&path))
+        else {
+            return;
+        };
+        for shortcut_entry in shortcut_entries {
+            if let Some(index) = shortcuts.find_existing(shortcut_entry) {
+                shortcut_entry.appid = shortcuts.shortcuts[index].appid;
+            }
+        }
+    }
+
     pub(crate) fn insert_shortcut(&self, shortcut_entry: ShortcutEntry) -> bool {
+        self.insert_shortcuts(vec![shortcut_entry])
+    }
+
+    /// Inserts all shortcuts with a single save of the shortcuts file. Their icon and
+    /// desktop file paths are stored the way the system Steam runs on sees them.
+    pub(crate) fn insert_shortcuts(&self, shortcut_entries: Vec<ShortcutEntry>) -> bool {
         let Some(shortcuts_file_path) = self.find_steam_shortcuts_file() else {
             return false;
         };
@@ -64,8 +363,26 @@ impl Config {
             return false;
         };
 
-        shortcuts.insert_shortcut(shortcut_entry);
-        shortcuts.save_to(&shortcuts_file_path);
+        let roots = self.get_root_mapping();
+        let mut inserted = Vec::new();
+        for mut shortcut_entry in shortcut_entries {
+            shortcut_entry.icon = roots.to_target(&shortcut_entry.icon);
+            shortcut_entry.shortcut_path = roots.to_target(&shortcut_entry.shortcut_path);
+            let verb = match shortcuts.find_existing(&shortcut_entry) {
+                Some(_) => "Updated",
+                None => "Added",
+            };
+            let app_name = shortcut_entry.app_name.clone();
+            let appid = shortcuts.insert_shortcut(shortcut_entry);
+            inserted.push((verb, app_name, generate_game_id(appid)));
+        }
+        if let Err(err) = shortcuts.save_to(&shortcuts_file_path) {
+            println!("Failed to save the shortcuts file - {err}");
+            return false;
+        }
+        for (verb, app_name, game_id) in inserted {
+            println!("{verb} {app_name}, launch it with steam://rungameid/{game_id}");
+        }
 
         true
     }
@@ -76,6 +393,125 @@ impl Config {
         Some(config_folder.join("grid"))
     }
 
+    /// A config for every user below `userdata`, creating their shortcuts file and grid
+    /// folder if they are missing.
+    pub(crate) fn get_all_user_configs(&self) -> anyhow::Result<Vec<(String, Config)>> {
+        let userdata = self
+            .get_steam_root()
+            .ok_or(anyhow!("Could not find the Steam root"))?
+            .join("userdata");
+
+        let mut rtn = Vec::new();
+        for dir_entry in userdata.read_dir()? {
+            let dir_entry = dir_entry?;
+            let Ok(user_id) = dir_entry.file_name().into_string() else {
+                continue;
+            };
+            if user_id == "0" || !dir_entry.path().is_dir() {
+                continue;
+            }
+
+            let config_folder = dir_entry.path().join("config");
+            std::fs::create_dir_all(config_folder.join("grid"))?;
+            let shortcuts_file_path = config_folder.join("shortcuts.vdf");
+            if !shortcuts_file_path.exists() {
+                Shortcuts::default().save_to(&shortcuts_file_path)?;
+            }
+
+            let mut config = self.clone();
+            config.all_users = false;
+            config.steam_user_id = Some(user_id.clone());
+            config.steam_shortcuts_file = Some(shortcuts_file_path);
+            rtn.push((user_id, config));
+        }
+        rtn.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(rtn)
+    }
+
+    pub(crate) fn get_steam_root(&self) -> Option<PathBuf> {
+        if let Some(steam_root) = &self.steam_root {
+            return Some(steam_root.canonicalize().unwrap_or(steam_root.clone()));
+        }
+        find_steam_roots(&self.get_home_dir()?).into_iter().next()
+    }
+
+    pub(crate) fn get_home_dir(&self) -> Option<PathBuf> {
+        self.home_dir.clone().or_else(dirs::home_dir)
+    }
+
+    /// `XDG_DATA_HOME` only applies to the real home folder, not to one given with `--home-dir`.
+    pub(crate) fn get_data_dir(&self) -> Option<PathBuf> {
+        match &self.home_dir {
+            Some(home_dir) => Some(home_dir.join(".local/share")),
+            None => dirs::data_dir(),
+        }
+    }
+
+    /// The `--locale`, else the locale of messages from the environment. `C` and `POSIX`
+    /// mean untranslated.
+    pub(crate) fn get_locale(&self) -> Option<String> {
+        self.locale
+            .clone()
+            .or_else(|| {
+                ["LC_ALL", "LC_MESSAGES", "LANG"]
+                    .into_iter()
+                    .filter_map(|name| std::env::var(name).ok())
+                    .find(|value| !value.is_empty())
+            })
+            .filter(|locale| {
+                let language = locale.split(['.', '@']).next().unwrap_or_default();
+                !language.is_empty() && language != "C" && language != "POSIX"
+            })
+    }
+
+    /// The user data folder, `XDG_DATA_DIRS` and the Flatpak exports, in the order desktop
+    /// files and icons are looked up in.
+    pub(crate) fn get_data_dirs(&self) -> Vec<PathBuf> {
+        let mut rtn: Vec<PathBuf> = self.get_data_dir().into_iter().collect();
+        let xdg_data_dirs = std::env::var_os("XDG_DATA_DIRS")
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| "/usr/local/share:/usr/share".into());
+        rtn.extend(std::env::split_paths(&xdg_data_dirs));
+        if let Some(flatpak_paths) = self.get_flatpak_paths() {
+            rtn.extend(flatpak_paths.share_dirs());
+        }
+
+        let mut seen = Vec::new();
+        rtn.retain(|dir| {
+            let is_new = !seen.contains(dir);
+            seen.push(dir.clone());
+            is_new
+        });
+        rtn
+    }
+
+    /// Where icons Steam can not show are stored after converting them to PNG.
+    pub(crate) fn get_icon_store(&self) -> Option<PathBuf> {
+        Some(self.get_data_dir()?.join("steam_icon_store"))
+    }
+
+    pub(crate) fn get_root_mapping(&self) -> RootMapping {
+        let mut roots = vec![(
+            self.flatpak_root.clone(),
+            PathBuf::from(FLATPAK_SYSTEM_ROOT),
+        )];
+        if let (Some(home_dir), Some(target_home_dir)) = (&self.home_dir, &self.target_home_dir) {
+            roots.push((home_dir.clone(), target_home_dir.clone()));
+        }
+        roots.retain(|(local, target)| local != target);
+        RootMapping { roots }
+    }
+
+    pub(crate) fn get_flatpak_paths(&self) -> Option<FlatpakPaths> {
+        let data_dir = self.get_data_dir()?;
+        Some(FlatpakPaths {
+            installations: vec![self.flatpak_root.clone(), data_dir.join("flatpak")],
+            icon_store: self.get_icon_store()?,
+            icon_theme: self.icon_theme.clone(),
+        })
+    }
+
     pub(crate) fn find_steam_shortcuts_file(&self) -> Option<PathBuf> {
         if let Some(steam_shortcuts_file) = &self.steam_shortcuts_file {
             return if steam_shortcuts_file.is_file() {
@@ -85,51 +521,146 @@ impl Config {
             };
         }
 
-        let mut rtn = dirs::home_dir()?;
-        rtn.push(".steam/steam/userdata/");
-        if let Some(steam_user_id) = &self.steam_user_id {
-            rtn.push(steam_user_id);
-        } else {
-            let user_id = match get_user_id_fast() {
-                Ok(value) => value,
-                Err(_) => rtn
-                    .read_dir()
-                    .unwrap()
-                    .find_map(|e| {
-                        if let Ok(i) = e {
-                            if i.file_name() != "0" && i.path().is_dir() {
-                                return None;
-                            }
-                            return Some(i.file_name());
-                        }
-                        None
-                    })?
-                    .to_str()?
-                    .to_string(),
-            };
-
-            rtn.push(user_id);
-        }
-        // if let Some(steam_user_id) = &self.steam_user_id {
-        //     rtn.push(steam_user_id);
-        // } else {
-        //     rtn = rtn
-        //         .read_dir()
-        //         .ok()?
-        //         .find(|e| {
-        //             if let Ok(i) = e {
-        //                 if i.file_name() != "0" && i.path().is_dir() {
-        //                     return true;
-        //                 }
-        //             }
-        //             false
-        //         })?
-        //         .ok()?
-        //         .path();
-        // }
+        let steam_root = self.get_steam_root()?;
+        let user_id = match find_user_id(&steam_root, self.steam_user_id.as_deref()) {
+            Ok(value) => value,
+            Err(err) => {
+                println!("Could not find the Steam user - {err}");
+                return None;
+            }
+        };
 
+        let mut rtn = steam_root.join("userdata");
+        rtn.push(user_id);
         rtn.push("config/shortcuts.vdf");
 
         if rtn.is_file() { Some(rtn) } else { None }
     }
 }
+
+/// The folders given on the command line and where they are on the system Steam runs on.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RootMapping {
+    roots: Vec<(PathBuf, PathBuf)>,
+}
+
+impl RootMapping {
+    /// The path as Steam sees it.
+    pub(crate) fn to_target(&self, path: &str) -> String {
+        Self::replace_root(
+            path,
+            self.roots.iter().map(|(local, target)| (local, target)),
+        )
+    }
+
+    /// The path on this system of a path Steam stored.
+    pub(crate) fn to_local(&self, path: &str) -> String {
+        Self::replace_root(
+            path,
+            self.roots.iter().map(|(local, target)| (target, local)),
+        )
+    }
+
+    /// Replaces the longest root the path is below.
+    fn replace_root<'a>(
+        path: &str,
+        roots: impl Iterator<Item = (&'a PathBuf, &'a PathBuf)>,
+    ) -> String {
+        roots
+            .filter_map(|(from, to)| Some((from, to, Path::new(path).strip_prefix(from).ok()?)))
+            .max_by_key(|(from, _, _)| from.as_os_str().len())
+            .map(|(_, to, rest)| match rest.as_os_str().is_empty() {
+                true => to.to_string_lossy().to_string(),
+                false => to.join(rest).to_string_lossy().to_string(),
+            })
+            .unwrap_or_else(|| path.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{Config, RootMapping};
+    use crate::shortcuts::Shortcuts;
+    use crate::shortcuts::shortcut::ShortcutEntry;
+    use crate::utils::TempDir;
+    use clap::Parser;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_get_all_user_configs() {
+        let steam_root = TempDir::new("all_users");
+        let userdata = steam_root.join("userdata");
+        let existing = userdata.join("111/config/shortcuts.vdf");
+        std::fs::create_dir_all(existing.parent().unwrap()).unwrap();
+        std::fs::create_dir_all(userdata.join("222")).unwrap();
+        std::fs::create_dir_all(userdata.join("0")).unwrap();
+        std::fs::write(userdata.join("notes.txt"), b"").unwrap();
+        let mut shortcuts = Shortcuts::default();
+        shortcuts.insert_shortcut(ShortcutEntry {
+            appid: 1,
+            app_name: "Game".to_string(),
+            ..Default::default()
+        });
+        shortcuts.save_to(&existing).unwrap();
+        let config = Config::parse_from([
+            "steam_shortcut",
+            "--steam-root",
+            steam_root.to_str().unwrap(),
+            "--steam-user-id",
+            "111",
+            "--all-users",
+        ]);
+
+        let configs = config.get_all_user_configs().unwrap();
+
+        let user_ids: Vec<&str> = configs.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(vec!["111", "222"], user_ids);
+        for (user_id, user_config) in &configs {
+            let shortcuts_file = userdata.join(user_id).join("config/shortcuts.vdf");
+            assert!(!user_config.all_users);
+            assert_eq!(Some(user_id), user_config.steam_user_id.as_ref());
+            assert_eq!(
+                Some(&shortcuts_file),
+                user_config.steam_shortcuts_file.as_ref()
+            );
+            assert!(userdata.join(user_id).join("config/grid").is_dir());
+        }
+        assert_eq!(1, Shortcuts::from_path(&existing).unwrap().shortcuts.len());
+        let created = Shortcuts::from_path(&userdata.join("222/config/shortcuts.vdf")).unwrap();
+        assert!(created.shortcuts.is_empty());
+        assert!(!userdata.join("0/config").exists());
+    }
+
+    #[test]
+    fn test_root_mapping() {
+        let roots = RootMapping {
+            roots: vec![
+                (
+                    PathBuf::from("/mnt/deck/flatpak"),
+                    PathBuf::from("/var/lib/flatpak"),
+                ),
+                (PathBuf::from("/mnt/deck"), PathBuf::from("/home/deck")),
+            ],
+        };
+
+        assert_eq!(
+            "/var/lib/flatpak/exports/share/applications/a.desktop",
+            roots.to_target("/mnt/deck/flatpak/exports/share/applications/a.desktop")
+        );
+        assert_eq!(
+            "/home/deck/.local/share/steam_icon_store/a.png",
+            roots.to_target("/mnt/deck/.local/share/steam_icon_store/a.png")
+        );
+        assert_eq!("/home/deck", roots.to_target("/mnt/deck"));
+        assert_eq!("/mnt/deckard/a.png", roots.to_target("/mnt/deckard/a.png"));
+        assert_eq!("", roots.to_target(""));
+        assert_eq!(
+            "/mnt/deck/flatpak/exports/a.desktop",
+            roots.to_local("/var/lib/flatpak/exports/a.desktop")
+        );
+        assert_eq!(
+            "/mnt/deck/.local/share/flatpak/exports/a.desktop",
+            roots.to_local("/home/deck/.local/share/flatpak/exports/a.desktop")
+        );
+    }
+}
@@ -0,0 +1,122 @@
+/// A `[Group]` of a desktop file with its keys in file order.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DesktopEntryGroup {
+    pub name: String,
+    pub entries: Vec<DesktopEntryValue>,
+}
+
+/// A `Key[locale]=value` line. The value is kept as written, with its escape sequences.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DesktopEntryValue {
+    pub key: String,
+    pub locale: Option<String>,
+    pub value: String,
+}
+
+impl DesktopEntryGroup {
+    /// The unlocalized value without escape sequences.
+    pub(crate) fn get_string(&self, key: &str) -> Option<String> {
+        self.get_raw(key, None).map(unescape)
+    }
+
+    /// The value of the best matching `Key[locale]`, falling back to the unlocalized value.
+    /// Locales are matched as `lang_COUNTRY@MODIFIER`, `lang_COUNTRY`, `lang@MODIFIER`, `lang`.
+    pub(crate) fn get_locale_string(&self, key: &str, locale: Option<&str>) -> Option<String> {
+        locale
+            .into_iter()
+            .flat_map(locale_candidates)
+            .find_map(|candidate| self.get_raw(key, Some(&candidate)))
+            .or_else(|| self.get_raw(key, None))
+            .map(unescape)
+    }
+
+    pub(crate) fn get_bool(&self, key: &str) -> bool {
+        self.get_raw(key, None).is_some_and(|v| v.trim() == "true")
+    }
+
+    /// A `;` separated list. `\;` is a semicolon inside an item.
+    pub(crate) fn get_strings(&self, key: &str) -> Vec<String> {
+        let Some(value) = self.get_raw(key, None) else {
+            return Vec::new();
+        };
+        let mut rtn = Vec::new();
+        let mut item = String::new();
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    item.push(c);
+                    item.extend(chars.next());
+                }
+                ';' => rtn.push(unescape(&std::mem::take(&mut item))),
+                c => item.push(c),
+            }
+        }
+        if !item.is_empty() {
+            rtn.push(unescape(&item));
+        }
+        rtn.retain(|item| !item.is_empty());
+        rtn
+    }
+
+    fn get_raw(&self, key: &str, locale: Option<&str>) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|e| e.key == key && e.locale.as_deref() == locale)
+            .map(|e| e.value.as_str())
+    }
+}
+
+/// Resolves `\s`, `\n`, `\t`, `\r`, `\\` and `\;`. Other sequences are kept as they are.
+fn unescape(value: &str) -> String {
+    let mut rtn = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            rtn.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => rtn.push(' '),
+            Some('n') => rtn.push('\n'),
+            Some('t') => rtn.push('\t'),
+            Some('r') => rtn.push('\r'),
+            Some('\\') => rtn.push('\\'),
+            Some(';') => rtn.push(';'),
+            Some(other) => {
+                rtn.push('\\');
+                rtn.push(other);
+            }
+            None => rtn.push('\\'),
+        }
+    }
+    rtn
+}
+
+/// The locale keys to try for a POSIX locale like `de_AT.UTF-8@euro`, best match first.
+fn locale_candidates(locale: &str) -> Vec<String> {
+    let (without_modifier, modifier) = match locale.split_once('@') {
+        Some((rest, modifier)) => (rest, Some(modifier)),
+        None => (locale, None),
+    };
+    let without_encoding = without_modifier
+        .split_once('.')
+        .map_or(without_modifier, |(rest, _)| rest);
+    let (lang, country) = match without_encoding.split_once('_') {
+        Some((lang, country)) => (lang, Some(country)),
+        None => (without_encoding, None),
+    };
+
+    let mut rtn = Vec::new();
+    if let (Some(country), Some(modifier)) = (country, modifier) {
+        rtn.push(format!("{lang}_{country}@{modifier}"));
+    }
+    if let Some(country) = country {
+        rtn.push(format!("{lang}_{country}"));
+    }
+    if let Some(modifier) = modifier {
+        rtn.push(format!("{lang}@{modifier}"));
+    }
+    rtn.push(lang.to_string());
+    rtn
+}
@@ -1,58 +1,823 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::path::Path;
+pub(crate) mod desktop_entry_group;
 
-#[derive(Debug)]
+use crate::config::AppIdAlgorithm;
+use crate::desktop_file::desktop_entry_group::{DesktopEntryGroup, DesktopEntryValue};
+use crate::icons::find_icon;
+use crate::shortcuts::shortcut::ShortcutEntry;
+use crate::utils::{find_executable, quote_argument, quote_path};
+use anyhow::anyhow;
+use std::path::{Path, PathBuf};
+
+const DESKTOP_ENTRY_GROUP: &str = "Desktop Entry";
+const DESKTOP_ACTION_PREFIX: &str = "Desktop Action ";
+
+/// The `[Desktop Entry]` group of a desktop file. String values are unescaped and
+/// unlocalized until [`DesktopFile::localize`] translates the names.
+#[derive(Debug, Clone, Default)]
 pub(crate) struct DesktopFile {
+    pub entry_type: String,
+    pub name: String,
+    pub generic_name: String,
+    pub icon: String,
+    pub exec: String,
+    pub try_exec: String,
+    pub path: String,
+    pub terminal: bool,
+    pub hidden: bool,
+    pub no_display: bool,
+    pub categories: Vec<String>,
+    pub keywords: Vec<String>,
+    pub startup_wm_class: String,
+    pub actions: Vec<DesktopAction>,
+    pub entry: DesktopEntryGroup,
+}
+
+/// A `[Desktop Action id]` group listed in the `Actions` key.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DesktopAction {
+    pub id: String,
     pub name: String,
     pub icon: String,
     pub exec: String,
+    pub group: DesktopEntryGroup,
+}
+
+impl DesktopAction {
+    /// The Exec key of the action split like [`DesktopFile::exec_arguments`]. `%i` is the
+    /// icon of the action if it has one.
+    pub(crate) fn exec_arguments(
+        &self,
+        desktop_file: &DesktopFile,
+        location: &Path,
+    ) -> anyhow::Result<Vec<String>> {
+        let icon = if self.icon.is_empty() {
+            &desktop_file.icon
+        } else {
+            &self.icon
+        };
+        expand_exec(
+            &self.exec,
+            &ExecContext {
+                icon,
+                name: &desktop_file.name,
+                location: &location.to_string_lossy(),
+            },
+        )
+        .map_err(|err| anyhow!("Action {} - {err}", self.id))
+    }
+
+    /// The name of the shortcut, e.g. `Firefox — New Private Window`.
+    pub(crate) fn shortcut_name(&self, app_name: &str) -> String {
+        format!("{app_name} — {}", self.name)
+    }
 }
 
 impl DesktopFile {
-    pub(crate) fn to_exec(&self) -> String {
-        if let Some(at) = self.exec.find(r#""run""#) {
-            return self.exec[at..].to_string();
+    /// The Exec key split into the program and its arguments, see [`expand_exec`].
+    /// `location` is the path of the desktop file, passed for `%k`.
+    pub(crate) fn exec_arguments(&self, location: &Path) -> anyhow::Result<Vec<String>> {
+        expand_exec(
+            &self.exec,
+            &ExecContext {
+                icon: &self.icon,
+                name: &self.name,
+                location: &location.to_string_lossy(),
+            },
+        )
+    }
+
+    /// Replaces `Name` and `GenericName`, also of the actions, with their translation for
+    /// the locale. Without a `Name` the application is named by its `GenericName`.
+    pub(crate) fn localize(&mut self, locale: Option<&str>) {
+        let translate = |group: &DesktopEntryGroup, key: &str| {
+            group.get_locale_string(key, locale).unwrap_or_default()
+        };
+        self.name = translate(&self.entry, "Name");
+        self.generic_name = translate(&self.entry, "GenericName");
+        if self.name.is_empty() {
+            self.name = self.generic_name.clone();
         }
-        match self.exec.find("run") {
-            None => self.exec.clone(),
-            Some(at) => self.exec[at..].to_string(),
+        for action in &mut self.actions {
+            action.name = translate(&action.group, "Name");
         }
     }
 
+    /// The actions with the given ids, or all of them. Unknown ids are an error.
+    pub(crate) fn select_actions(
+        &self,
+        ids: &[String],
+        all: bool,
+    ) -> anyhow::Result<Vec<&DesktopAction>> {
+        if all {
+            return Ok(self.actions.iter().collect());
+        }
+        ids.iter()
+            .map(|id| {
+                self.actions.iter().find(|a| &a.id == id).ok_or(anyhow!(
+                    "{} has no action {id}, it has {}",
+                    self.name,
+                    self.list_actions()
+                ))
+            })
+            .collect()
+    }
+
+    /// The actions as `id (Name)`, comma separated.
+    pub(crate) fn list_actions(&self) -> String {
+        if self.actions.is_empty() {
+            return "no actions".to_string();
+        }
+        self.actions
+            .iter()
+            .map(|a| format!("{} ({})", a.id, a.name))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
     pub fn try_from_path(path: &Path) -> Option<Self> {
-        let mut name = String::new();
-        let mut icon = String::new();
-        let mut exec = String::new();
-
-        let file = File::open(path).ok()?;
-        // Parse
-        let reader = BufReader::new(file);
-        for line_result in reader.lines() {
-            let tmp = line_result.ok()?;
-            let line = tmp.trim();
-            if let Some((key, value)) = line.split_once("=") {
-                match key {
-                    "Name" => {
-                        if name.is_empty() {
-                            name = value.to_string()
-                        }
-                    }
-                    "Icon" => {
-                        if icon.is_empty() {
-                            icon = value.to_string()
-                        }
-                    }
-                    "Exec" => {
-                        if exec.is_empty() {
-                            exec = value.to_string()
-                        }
+        Self::from_path(path).ok()
+    }
+
+    pub(crate) fn from_path(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Self::from_str(&content).map_err(|err| anyhow!("{} - {err}", path.display()))
+    }
+
+    pub(crate) fn from_str(content: &str) -> anyhow::Result<Self> {
+        let groups = parse_groups(content);
+        let entry = groups
+            .iter()
+            .find(|g| g.name == DESKTOP_ENTRY_GROUP)
+            .ok_or(anyhow!("There is no [{DESKTOP_ENTRY_GROUP}] group"))?
+            .clone();
+        let string = |key: &str| entry.get_string(key).unwrap_or_default();
+
+        let actions = entry
+            .get_strings("Actions")
+            .into_iter()
+            .filter_map(|id| {
+                let group = groups
+                    .iter()
+                    .find(|g| g.name.strip_prefix(DESKTOP_ACTION_PREFIX) == Some(id.as_str()))?
+                    .clone();
+                Some(DesktopAction {
+                    name: group.get_string("Name").unwrap_or_default(),
+                    icon: group.get_string("Icon").unwrap_or_default(),
+                    exec: group.get_string("Exec").unwrap_or_default(),
+                    id,
+                    group,
+                })
+            })
+            .collect();
+
+        Ok(Self {
+            entry_type: string("Type"),
+            name: string("Name"),
+            generic_name: string("GenericName"),
+            icon: string("Icon"),
+            exec: string("Exec"),
+            try_exec: string("TryExec"),
+            path: string("Path"),
+            terminal: entry.get_bool("Terminal"),
+            hidden: entry.get_bool("Hidden"),
+            no_display: entry.get_bool("NoDisplay"),
+            categories: entry.get_strings("Categories"),
+            keywords: entry.get_strings("Keywords"),
+            startup_wm_class: string("StartupWMClass"),
+            actions,
+            entry,
+        })
+    }
+}
+
+/// The arguments to pass to `flatpak` to start the app of an expanded Exec. Exported desktop
+/// files run `flatpak [options] run ...`, any other program is run inside the app with
+/// `--command`.
+pub(crate) fn flatpak_run_arguments(arguments: &[String], flatpak_id: &str) -> Vec<String> {
+    let is_flatpak = arguments
+        .first()
+        .and_then(|program| Path::new(program).file_name())
+        .is_some_and(|name| name == "flatpak");
+    if is_flatpak
+        && arguments[1..]
+            .iter()
+            .find(|argument| !argument.starts_with('-'))
+            .is_some_and(|command| command == "run")
+    {
+        return arguments[1..].to_vec();
+    }
+
+    let mut rtn = vec!["run".to_string()];
+    if let Some(program) = arguments.first() {
+        rtn.push(format!("--command={program}"));
+    }
+    rtn.push(flatpak_id.to_string());
+    rtn.extend(arguments.iter().skip(1).cloned());
+    rtn
+}
+
+/// Splits the file into its groups. Comments, blank lines, lines before the first group
+/// and lines that are not `Key=value` or `Key[locale]=value` are skipped.
+pub(crate) fn parse_groups(content: &str) -> Vec<DesktopEntryGroup> {
+    let mut rtn: Vec<DesktopEntryGroup> = Vec::new();
+    for line in content.lines() {
+        let line = line.trim_start();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line
+            .trim_end()
+            .strip_prefix('[')
+            .and_then(|l| l.strip_suffix(']'))
+        {
+            rtn.push(DesktopEntryGroup {
+                name: name.to_string(),
+                entries: Vec::new(),
+            });
+            continue;
+        }
+        let (Some(group), Some((key, value))) = (rtn.last_mut(), line.split_once('=')) else {
+            continue;
+        };
+        let key = key.trim_end();
+        let (key, locale) = match key.strip_suffix(']').and_then(|k| k.split_once('[')) {
+            Some((key, locale)) => (key, Some(locale.to_string())),
+            None => (key, None),
+        };
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            continue;
+        }
+        let is_duplicate = group
+            .entries
+            .iter()
+            .any(|e| e.key == key && e.locale == locale);
+        if !is_duplicate {
+            group.entries.push(DesktopEntryValue {
+                key: key.to_string(),
+                locale,
+                value: value.trim_start().to_string(),
+            });
+        }
+    }
+    rtn
+}
+
+/// Reads a desktop file with the names in the locale and checks that it is an installed
+/// application.
+pub(crate) fn read_application(
+    desktop_file_path: &Path,
+    locale: Option<&str>,
+) -> anyhow::Result<DesktopFile> {
+    let mut desktop_file = DesktopFile::from_path(desktop_file_path)?;
+    desktop_file.localize(locale);
+    if desktop_file.entry_type != "Application" {
+        return Err(anyhow!(
+            "Only applications can be started, this is a {}",
+            desktop_file.entry_type
+        ));
+    }
+    if desktop_file.hidden {
+        return Err(anyhow!("The desktop file is marked as deleted with Hidden"));
+    }
+    if !desktop_file.try_exec.is_empty()
+        && find_executable(Path::new(&desktop_file.try_exec)).is_err()
+    {
+        return Err(anyhow!("{} is not installed", desktop_file.try_exec));
+    }
+    if desktop_file.terminal {
+        println!("The application runs in a terminal, which Steam does not open");
+    }
+    if desktop_file.no_display {
+        println!("The application is hidden from menus, it might not be meant to be started");
+    }
+    Ok(desktop_file)
+}
+
+/// Builds a shortcut that runs the Exec of the desktop file, or of one of its actions, the
+/// way a launcher would. An action without an icon of its own uses the icon of the file.
+pub(crate) fn create_desktop_file_shortcut(
+    desktop_file_path: &Path,
+    desktop_file: &DesktopFile,
+    action: Option<&DesktopAction>,
+    share_dirs: &[PathBuf],
+    icon_store: &Path,
+    icon_theme: Option<&str>,
+    app_id_algorithm: AppIdAlgorithm,
+) -> anyhow::Result<ShortcutEntry> {
+    let (app_name, arguments) = match action {
+        Some(action) => (
+            action.shortcut_name(&desktop_file.name),
+            action.exec_arguments(desktop_file, desktop_file_path)?,
+        ),
+        None => (
+            desktop_file.name.clone(),
+            desktop_file.exec_arguments(desktop_file_path)?,
+        ),
+    };
+    let exe = find_executable(Path::new(&arguments[0]))?;
+    let start_dir = if desktop_file.path.is_empty() {
+        exe.parent().unwrap_or(Path::new("/")).to_path_buf()
+    } else {
+        PathBuf::from(&desktop_file.path)
+    };
+
+    let exe = quote_path(&exe.to_string_lossy());
+    let launch_options = arguments[1..]
+        .iter()
+        .map(|argument| quote_argument(argument))
+        .collect::<Vec<String>>()
+        .join(" ");
+    let mut tags = Vec::new();
+    for category in &desktop_file.categories {
+        if !tags.contains(category) {
+            tags.push(category.clone());
+        }
+    }
+
+    Ok(ShortcutEntry {
+        appid: app_id_algorithm.generate_app_id(&exe, &app_name, &launch_options),
+        exe,
+        start_dir: quote_path(&start_dir.to_string_lossy()),
+        icon: find_action_icon(desktop_file, action, share_dirs, icon_store, icon_theme),
+        shortcut_path: desktop_file_path.to_string_lossy().to_string(),
+        launch_options,
+        allow_desktop_config: 1,
+        allow_overlay: 1,
+        app_name,
+        tags,
+        ..Default::default()
+    })
+}
+
+/// The icon of the action if it has one that can be found, else the icon of the file.
+pub(crate) fn find_action_icon(
+    desktop_file: &DesktopFile,
+    action: Option<&DesktopAction>,
+    share_dirs: &[PathBuf],
+    icon_store: &Path,
+    icon_theme: Option<&str>,
+) -> String {
+    action
+        .filter(|action| !action.icon.is_empty())
+        .map(|action| find_icon(&action.icon, share_dirs, icon_store, icon_theme))
+        .filter(|icon| !icon.is_empty())
+        .unwrap_or_else(|| find_icon(&desktop_file.icon, share_dirs, icon_store, icon_theme))
+}
+
+/// Resolves a path or a desktop file id like `org.gnome.Calculator` or
+/// `kde-org.kde.konsole.desktop`. Ids are looked up in the `applications` folder of every
+/// data folder, where a `-` in the id may stand for a subfolder. Earlier data folders win.
+/// A name that is no id is matched against the StartupWMClass and Keywords of the
+/// applications, like `firefox` for `org.mozilla.firefox`, if only one of them has it.
+pub(crate) fn find_desktop_file(
+    path_or_id: &str,
+    data_dirs: &[PathBuf],
+) -> anyhow::Result<PathBuf> {
+    let path = Path::new(path_or_id);
+    if path.is_file() {
+        return Ok(path.to_path_buf());
+    }
+    if path.components().count() > 1 {
+        return Err(anyhow!("{path_or_id} does not exist"));
+    }
+
+    let id = if path_or_id.ends_with(".desktop") {
+        path_or_id.to_string()
+    } else {
+        format!("{path_or_id}.desktop")
+    };
+    if let Some(rtn) = data_dirs
+        .iter()
+        .find_map(|data_dir| find_desktop_file_id(&data_dir.join("applications"), "", &id))
+    {
+        return Ok(rtn);
+    }
+
+    let name = path_or_id.trim_end_matches(".desktop");
+    let mut matches = Vec::new();
+    for data_dir in data_dirs {
+        find_desktop_files_by_name(&data_dir.join("applications"), "", name, &mut matches);
+    }
+    match matches.as_slice() {
+        [] => Err(anyhow!("Could not find the desktop file {id}")),
+        [(_, rtn)] => Ok(rtn.clone()),
+        _ => Err(anyhow!(
+            "Several desktop files match {name}, pick one of {}",
+            matches
+                .iter()
+                .map(|(id, _)| id.as_str())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        )),
+    }
+}
+
+/// Adds the id and path of every application below the folder whose StartupWMClass or one
+/// of whose Keywords is `name`, ignoring case. Ids already found in an earlier data folder
+/// are skipped.
+fn find_desktop_files_by_name(
+    folder: &Path,
+    prefix: &str,
+    name: &str,
+    rtn: &mut Vec<(String, PathBuf)>,
+) {
+    let Ok(dir_entries) = folder.read_dir() else {
+        return;
+    };
+    for dir_entry in dir_entries.flatten() {
+        let path = dir_entry.path();
+        let Some(file_name) = dir_entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        let id = format!("{prefix}{file_name}");
+        if path.is_dir() {
+            find_desktop_files_by_name(&path, &format!("{id}-"), name, rtn);
+            continue;
+        }
+        if !file_name.ends_with(".desktop") || rtn.iter().any(|(found, _)| *found == id) {
+            continue;
+        }
+        let Ok(desktop_file) = DesktopFile::from_path(&path) else {
+            continue;
+        };
+        if desktop_file.entry_type == "Application"
+            && (desktop_file.startup_wm_class.eq_ignore_ascii_case(name)
+                || desktop_file
+                    .keywords
+                    .iter()
+                    .any(|keyword| keyword.eq_ignore_ascii_case(name)))
+        {
+            rtn.push((id, path));
+        }
+    }
+}
+
+fn find_desktop_file_id(folder: &Path, prefix: &str, id: &str) -> Option<PathBuf> {
+    let file = folder.join(id.strip_prefix(prefix)?);
+    if file.is_file() {
+        return Some(file);
+    }
+    for dir_entry in folder.read_dir().ok()? {
+        let dir_entry = dir_entry.ok()?;
+        let Some(name) = dir_entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        let sub_prefix = format!("{prefix}{name}-");
+        if dir_entry.path().is_dir()
+            && id.starts_with(&sub_prefix)
+            && let Some(rtn) = find_desktop_file_id(&dir_entry.path(), &sub_prefix, id)
+        {
+            return Some(rtn);
+        }
+    }
+    None
+}
+
+/// What the field codes of an Exec expand to besides `%%`.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct ExecContext<'a> {
+    /// `%i` becomes `--icon` and this, nothing if it is empty.
+    pub icon: &'a str,
+    /// `%c`, the translated name of the application.
+    pub name: &'a str,
+    /// `%k`, the path of the desktop file.
+    pub location: &'a str,
+}
+
+/// Field codes that are replaced by nothing, as no files or URLs are passed from Steam.
+/// `%d`, `%D`, `%n`, `%N`, `%v` and `%m` are deprecated.
+const DROPPED_FIELD_CODES: [char; 10] = ['f', 'F', 'u', 'U', 'd', 'D', 'n', 'N', 'v', 'm'];
+
+/// Splits an Exec value into the program and its arguments as the Desktop Entry
+/// specification describes. Inside double quotes `\"`, `` \` ``, `\$` and `\\` are escapes and
+/// `%` is literal. Outside of them field codes are expanded with `context` and `%%` is `%`.
+pub(crate) fn expand_exec(exec: &str, context: &ExecContext) -> anyhow::Result<Vec<String>> {
+    let mut rtn = Vec::new();
+    let mut argument = String::new();
+    let mut has_argument = false;
+    let mut in_quotes = false;
+    let mut chars = exec.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_argument = true;
+            }
+            '\\' if in_quotes => match chars.next() {
+                Some(escaped @ ('"' | '`' | '$' | '\\')) => argument.push(escaped),
+                _ => return Err(anyhow!("Invalid escape sequence in Exec {exec}")),
+            },
+            ' ' | '\t' | '\n' if !in_quotes => {
+                if has_argument {
+                    rtn.push(std::mem::take(&mut argument));
+                    has_argument = false;
+                }
+            }
+            '%' if !in_quotes => match chars.next() {
+                Some('%') => {
+                    argument.push('%');
+                    has_argument = true;
+                }
+                Some('i') if !context.icon.is_empty() => {
+                    if has_argument {
+                        rtn.push(std::mem::take(&mut argument));
                     }
-                    &_ => {}
+                    rtn.push("--icon".to_string());
+                    argument.push_str(context.icon);
+                    has_argument = true;
+                }
+                Some('i') => {}
+                Some(code @ ('c' | 'k')) => {
+                    let value = if code == 'c' {
+                        context.name
+                    } else {
+                        context.location
+                    };
+                    argument.push_str(value);
+                    has_argument |= !value.is_empty();
                 }
+                Some(code) if DROPPED_FIELD_CODES.contains(&code) => {}
+                _ => return Err(anyhow!("Invalid field code in Exec {exec}")),
+            },
+            c => {
+                argument.push(c);
+                has_argument = true;
             }
         }
+    }
+    if in_quotes {
+        return Err(anyhow!("Unterminated quote in Exec {exec}"));
+    }
+    if has_argument {
+        rtn.push(argument);
+    }
+    if rtn.is_empty() {
+        return Err(anyhow!("Exec is empty"));
+    }
+
+    Ok(rtn)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::AppIdAlgorithm;
+    use crate::desktop_file::{
+        DesktopFile, ExecContext, create_desktop_file_shortcut, expand_exec, find_desktop_file,
+    };
+    use crate::utils::TempDir;
+    use std::path::Path;
+
+    #[test]
+    fn test_parse_desktop_file() {
+        let desktop_file = DesktopFile::from_str(
+            r#"# Comment
+[Desktop Entry]
+Type=Application
+Name=Browser
+Name[de]=Netzbrowser
+Name[de_AT]=Browser AT
+Name[sr@latin]=Pregledač
+GenericName = Web Browser
+GenericName[de]=Webbrowser
+Exec=browser --title "A\sB" "C\\$D" %u
+TryExec=browser
+Path=/opt/browser
+Icon=browser
+Terminal=false
+NoDisplay=true
+Categories=Network;WebBrowser;
+Keywords=web;semi\;colon;tab\there
+StartupWMClass=Browser
+Actions=new-window;private;missing;
+
+[Desktop Action new-window]
+Name=New Window
+Exec=browser --new-window
+
+[Desktop Action private]
+Name=Private Window
+Name[de]=Privates Fenster
+Icon=browser-private
+Exec=browser --private
+"#,
+        )
+        .unwrap();
+
+        assert_eq!("Application", desktop_file.entry_type);
+        assert_eq!("Browser", desktop_file.name);
+        assert_eq!("Web Browser", desktop_file.generic_name);
+        assert_eq!(r#"browser --title "A B" "C\$D" %u"#, desktop_file.exec);
+        assert_eq!(
+            vec!["browser", "--title", "A B", "C$D"],
+            desktop_file
+                .exec_arguments(Path::new("/usr/share/applications/browser.desktop"))
+                .unwrap()
+        );
+        assert_eq!("browser", desktop_file.try_exec);
+        assert_eq!("/opt/browser", desktop_file.path);
+        assert_eq!("browser", desktop_file.icon);
+        assert!(!desktop_file.terminal);
+        assert!(desktop_file.no_display);
+        assert!(!desktop_file.hidden);
+        assert_eq!(vec!["Network", "WebBrowser"], desktop_file.categories);
+        assert_eq!(
+            vec!["web", "semi;colon", "tab\there"],
+            desktop_file.keywords
+        );
+        assert_eq!("Browser", desktop_file.startup_wm_class);
+
+        assert_eq!(2, desktop_file.actions.len());
+        assert_eq!("new-window", desktop_file.actions[0].id);
+        assert_eq!("New Window", desktop_file.actions[0].name);
+        assert_eq!("", desktop_file.actions[0].icon);
+        assert_eq!("browser-private", desktop_file.actions[1].icon);
+        assert_eq!("browser --private", desktop_file.actions[1].exec);
+
+        let name = |locale| {
+            desktop_file
+                .entry
+                .get_locale_string("Name", locale)
+                .unwrap()
+        };
+        assert_eq!("Browser", name(None));
+        assert_eq!("Netzbrowser", name(Some("de_DE.UTF-8")));
+        assert_eq!("Browser AT", name(Some("de_AT.UTF-8@euro")));
+        assert_eq!("Pregledač", name(Some("sr_RS@latin")));
+        assert_eq!("Browser", name(Some("fr_FR")));
+        assert_eq!(
+            "Privates Fenster",
+            desktop_file.actions[1]
+                .group
+                .get_locale_string("Name", Some("de"))
+                .unwrap()
+        );
+
+        let mut german = desktop_file.clone();
+        german.localize(Some("de_DE.UTF-8"));
+        assert_eq!("Netzbrowser", german.name);
+        assert_eq!("Webbrowser", german.generic_name);
+        assert_eq!("New Window", german.actions[0].name);
+        assert_eq!("Privates Fenster", german.actions[1].name);
+        german.localize(None);
+        assert_eq!("Browser", german.name);
+        assert_eq!("Private Window", german.actions[1].name);
+
+        let mut generic = DesktopFile::from_str(
+            "[Desktop Entry]\nGenericName=Calculator\nGenericName[ja]=電卓\n",
+        )
+        .unwrap();
+        generic.localize(Some("ja_JP.UTF-8"));
+        assert_eq!("電卓", generic.name);
+
+        assert!(DesktopFile::from_str("Name=No group").is_err());
+    }
+
+    #[test]
+    fn test_expand_exec() {
+        let split_exec = |exec| expand_exec(exec, &ExecContext::default());
+        assert_eq!(
+            vec!["firefox", "--new-window"],
+            split_exec("firefox --new-window %u").unwrap()
+        );
+        assert_eq!(
+            vec!["/opt/My Game/run.sh", "say \"hi\" $HOME", "100%"],
+            split_exec(r#""/opt/My Game/run.sh"  "say \"hi\" \$HOME" 100%%"#).unwrap()
+        );
+        assert_eq!(vec!["app", ""], split_exec(r#"app """#).unwrap());
+        assert_eq!(vec!["app", "50%f"], split_exec(r#"app "50%f""#).unwrap());
+        assert_eq!(vec!["app", r"a\b"], split_exec(r"app a\b %i %c").unwrap());
+        assert!(split_exec(r#""unterminated"#).is_err());
+        assert!(split_exec(r#""bad \escape""#).is_err());
+        assert!(split_exec("app %z").is_err());
+        assert!(split_exec(" %F ").is_err());
+
+        let context = ExecContext {
+            icon: "org.example.App",
+            name: "Example App",
+            location: "/usr/share/applications/org.example.App.desktop",
+        };
+        assert_eq!(
+            vec![
+                "app",
+                "--icon",
+                "org.example.App",
+                "--name=Example App",
+                "/usr/share/applications/org.example.App.desktop",
+            ],
+            expand_exec("app %i --name=%c %k %U", &context).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_desktop_action_shortcuts() {
+        let dir = TempDir::new("actions");
+        let icon = dir.join("browser.png");
+        std::fs::write(&icon, b"png").unwrap();
+        let desktop_file_path = dir.join("browser.desktop");
+        let desktop_file = DesktopFile::from_str(&format!(
+            r#"[Desktop Entry]
+Type=Application
+Name=Browser
+Exec=/bin/sh --window %u
+Icon={}
+Actions=private;new-window;
+
+[Desktop Action private]
+Name=Private Window
+Icon=missing-icon
+Exec=/bin/sh --private %%
+
+[Desktop Action new-window]
+Name=New Window
+Exec=
+"#,
+            icon.display()
+        ))
+        .unwrap();
+
+        let private = desktop_file
+            .select_actions(&["private".to_string()], false)
+            .unwrap();
+        assert_eq!(1, private.len());
+        assert_eq!(2, desktop_file.select_actions(&[], true).unwrap().len());
+        assert!(desktop_file.select_actions(&[], false).unwrap().is_empty());
+        assert!(
+            desktop_file
+                .select_actions(&["unknown".to_string()], false)
+                .is_err()
+        );
+
+        let shortcut = |action| {
+            create_desktop_file_shortcut(
+                &desktop_file_path,
+                &desktop_file,
+                action,
+                &[],
+                &dir,
+                None,
+                AppIdAlgorithm::Steam,
+            )
+        };
+        let main = shortcut(None).unwrap();
+        let private = shortcut(Some(private[0])).unwrap();
+        assert_eq!("\"/bin/sh\"", main.exe);
+        assert_eq!("\"/bin\"", main.start_dir);
+        assert_eq!("Browser — Private Window", private.app_name);
+        assert_eq!("--private %", private.launch_options);
+        assert_eq!(icon.to_string_lossy(), private.icon);
+        assert_ne!(main.appid, private.appid);
+        assert!(shortcut(Some(&desktop_file.actions[1])).is_err());
+    }
+
+    #[test]
+    fn test_find_desktop_file() {
+        let dir = TempDir::new("desktop");
+        let user = dir.join("user");
+        let system = dir.join("system");
+        std::fs::create_dir_all(user.join("applications")).unwrap();
+        std::fs::create_dir_all(system.join("applications/kde-org")).unwrap();
+        std::fs::write(user.join("applications/app.desktop"), "").unwrap();
+        std::fs::write(system.join("applications/app.desktop"), "").unwrap();
+        std::fs::write(system.join("applications/kde-org/konsole.desktop"), "").unwrap();
+        let data_dirs = vec![user.clone(), system.clone()];
+
+        assert_eq!(
+            user.join("applications/app.desktop"),
+            find_desktop_file("app", &data_dirs).unwrap()
+        );
+        assert_eq!(
+            system.join("applications/kde-org/konsole.desktop"),
+            find_desktop_file("kde-org-konsole.desktop", &data_dirs).unwrap()
+        );
+        let path = system.join("applications/app.desktop");
+        assert_eq!(
+            path,
+            find_desktop_file(path.to_str().unwrap(), &data_dirs).unwrap()
+        );
+        assert!(find_desktop_file("missing", &data_dirs).is_err());
 
-        Some(Self { name, icon, exec })
+        let application = |extra: &str| format!("[Desktop Entry]\nType=Application\n{extra}\n");
+        std::fs::write(
+            system.join("applications/kde-org/browser.desktop"),
+            application("StartupWMClass=Browser\nKeywords=web;"),
+        )
+        .unwrap();
+        std::fs::write(
+            user.join("applications/mail.desktop"),
+            application("Keywords=Post;Web;"),
+        )
+        .unwrap();
+        assert_eq!(
+            system.join("applications/kde-org/browser.desktop"),
+            find_desktop_file("browser", &data_dirs).unwrap()
+        );
+        assert_eq!(
+            user.join("applications/mail.desktop"),
+            find_desktop_file("post", &data_dirs).unwrap()
+        );
+        let err = find_desktop_file("web", &data_dirs).unwrap_err();
+        assert!(err.to_string().contains("kde-org-browser.desktop"));
+        assert!(err.to_string().contains("mail.desktop"));
     }
 }
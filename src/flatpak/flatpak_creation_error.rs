@@ -3,4 +3,6 @@ pub enum FlatPakCreationError {
     #[allow(dead_code)]
     DesktopFileNotFound(String),
     CannotParseDesktopFile,
+    #[allow(dead_code)]
+    InvalidExec(String),
 }
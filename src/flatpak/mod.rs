@@ -1,39 +1,109 @@
 pub(crate) mod flatpak_creation_error;
 
-use crate::desktop_file::DesktopFile;
+use crate::config::AppIdAlgorithm;
+use crate::desktop_file::{DesktopAction, DesktopFile, find_action_icon, flatpak_run_arguments};
 use crate::flatpak::flatpak_creation_error::FlatPakCreationError;
+use crate::icons::find_icon;
 use crate::shortcuts::shortcut::ShortcutEntry;
-use crate::utils::{create_png_from_svg, fix_launch_options, generate_app_id};
-use std::path::Path;
+use crate::utils::quote_launch_options;
+use std::path::{Path, PathBuf};
 // pub(crate) fn add_flatpak_to_steam(flatpak_id: &str) {}
 
-pub(crate) fn create_flatpak_shortcut(
-    flatpak_id: &str,
-) -> Result<ShortcutEntry, FlatPakCreationError> {
-    let desktop_file_path = Path::new("/var/lib/flatpak/exports/share/applications/")
-        .join(format!("{flatpak_id}.desktop"));
+/// Folders the exported desktop files and icons of Flatpak installations are read from,
+/// the folder converted icons are written to and the icon theme to look them up in.
+#[derive(Debug, Clone)]
+pub(crate) struct FlatpakPaths {
+    pub installations: Vec<PathBuf>,
+    pub icon_store: PathBuf,
+    pub icon_theme: Option<String>,
+}
 
-    if !desktop_file_path.is_file() {
+impl FlatpakPaths {
+    /// The `share` folders the installations export desktop files and icons to.
+    pub(crate) fn share_dirs(&self) -> Vec<PathBuf> {
+        self.installations
+            .iter()
+            .map(|installation| installation.join("exports/share"))
+            .collect()
+    }
+
+    fn find_export(&self, relative_path: &Path) -> Option<PathBuf> {
+        self.share_dirs()
+            .into_iter()
+            .map(|share_dir| share_dir.join(relative_path))
+            .find(|path| path.is_file())
+    }
+}
+
+/// The exported desktop file of the Flatpak, with the names in the locale.
+pub(crate) fn find_flatpak_desktop_file(
+    flatpak_id: &str,
+    paths: &FlatpakPaths,
+    locale: Option<&str>,
+) -> Result<(PathBuf, DesktopFile), FlatPakCreationError> {
+    let Some(desktop_file_path) =
+        paths.find_export(&Path::new("applications").join(format!("{flatpak_id}.desktop")))
+    else {
         return Err(FlatPakCreationError::DesktopFileNotFound(format!(
-            "Could not find a desktop file at {}",
-            desktop_file_path.display()
+            "Could not find a desktop file for {flatpak_id} in {}",
+            paths
+                .installations
+                .iter()
+                .map(|installation| installation.display().to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
         )));
-    }
+    };
 
-    let desktop_file: DesktopFile = DesktopFile::try_from_path(&desktop_file_path)
+    let mut desktop_file: DesktopFile = DesktopFile::try_from_path(&desktop_file_path)
         .ok_or(FlatPakCreationError::CannotParseDesktopFile)?;
-    const EXE: &str = "/usr/bin/flatpak";
-    let appid = generate_app_id("/usr/bin/flatpak", flatpak_id);
+    desktop_file.localize(locale);
+    Ok((desktop_file_path, desktop_file))
+}
+
+pub(crate) const FLATPAK_EXE: &str = "/usr/bin/flatpak";
 
-    let icon = get_icon_path(&desktop_file.icon);
+/// Builds the shortcut of the Flatpak, or of one of the actions of its desktop file. Icons
+/// are looked up in `data_dirs`, see [`crate::config::Config::get_data_dirs`].
+pub(crate) fn create_flatpak_shortcut(
+    flatpak_id: &str,
+    desktop_file_path: &Path,
+    desktop_file: &DesktopFile,
+    action: Option<&DesktopAction>,
+    paths: &FlatpakPaths,
+    data_dirs: &[PathBuf],
+    app_id_algorithm: AppIdAlgorithm,
+) -> Result<ShortcutEntry, FlatPakCreationError> {
+    let (app_name, arguments, legacy_name) = match action {
+        Some(action) => (
+            action.shortcut_name(&desktop_file.name),
+            action.exec_arguments(desktop_file, desktop_file_path),
+            format!("{flatpak_id} {}", action.id),
+        ),
+        None => (
+            desktop_file.name.clone(),
+            desktop_file.exec_arguments(desktop_file_path),
+            flatpak_id.to_string(),
+        ),
+    };
+    let arguments = arguments.map_err(|err| FlatPakCreationError::InvalidExec(err.to_string()))?;
+    let appid = app_id_algorithm.generate_app_id(FLATPAK_EXE, &app_name, &legacy_name);
+
+    let icon = find_action_icon(
+        desktop_file,
+        action,
+        data_dirs,
+        &paths.icon_store,
+        paths.icon_theme.as_deref(),
+    );
 
-    let launch_options = fix_launch_options(desktop_file.to_exec());
+    let launch_options = quote_launch_options(&flatpak_run_arguments(&arguments, flatpak_id));
 
     Ok(ShortcutEntry {
         appid,
-        app_name: desktop_file.name,
-        exe: EXE.to_string(),
-        start_dir: "/usr/bin".to_string(),
+        app_name,
+        exe: app_id_algorithm.format_path(FLATPAK_EXE),
+        start_dir: app_id_algorithm.format_path("/usr/bin"),
         icon,
         shortcut_path: desktop_file_path.as_os_str().to_str().unwrap().to_string(),
         launch_options,
@@ -48,46 +118,51 @@ pub(crate) fn create_flatpak_shortcut(
         flatpak_app_id: flatpak_id.to_string(),
         sort_as: "".to_string(),
         tags: vec![],
+        extra_fields: vec![],
+        key_order: vec![],
+        flatpak_action_id: action.map(|action| action.id.clone()).unwrap_or_default(),
     })
 }
 
-pub(crate) fn get_icon_path(icon: &str) -> String {
-    let root = Path::new("/var/lib/flatpak/exports/share/icons/hicolor/");
-    let scalable = root.join("scalable/apps/");
-
-    let svg = scalable.join(format!("{icon}.svg"));
-    if svg.is_file() {
-        let sis = dirs::data_dir().unwrap().join("steam_icon_store");
-        std::fs::create_dir_all(&sis).unwrap();
-        let rtn_path = sis.join(format!("{icon}.png"));
-
-        create_png_from_svg(&svg, &rtn_path);
-        return rtn_path.as_os_str().to_str().unwrap().to_string();
-    }
+/// Looks the icon up in `data_dirs`, see [`crate::config::Config::get_data_dirs`].
+pub(crate) fn get_icon_path(icon: &str, paths: &FlatpakPaths, data_dirs: &[PathBuf]) -> String {
+    find_icon(
+        icon,
+        data_dirs,
+        &paths.icon_store,
+        paths.icon_theme.as_deref(),
+    )
+}
 
-    let o512x512 = root.join("512x512/apps/");
-    let png = o512x512.join(format!("{icon}.png"));
-    if png.is_file() {
-        return png.as_os_str().to_str().unwrap().to_string();
-    }
+#[cfg(test)]
+mod tests {
+    use crate::flatpak::{FlatpakPaths, get_icon_path};
+    use crate::utils::TempDir;
 
-    let o256x256 = root.join("256x256/apps/");
-    let png = o256x256.join(format!("{icon}.png"));
-    if png.is_file() {
-        return png.as_os_str().to_str().unwrap().to_string();
-    }
+    #[test]
+    fn test_get_icon_path_searches_all_installations() {
+        let dir = TempDir::new("flatpak");
+        let system = dir.join("system");
+        let user = dir.join("user");
+        let icon_folder = user.join("exports/share/icons/hicolor/256x256/apps");
+        std::fs::create_dir_all(&icon_folder).unwrap();
+        std::fs::create_dir_all(system.join("exports/share/icons/hicolor/512x512/apps")).unwrap();
+        std::fs::write(icon_folder.join("org.example.App.png"), b"png").unwrap();
+        let paths = FlatpakPaths {
+            installations: vec![system, user],
+            icon_store: dir.join("steam_icon_store"),
+            icon_theme: None,
+        };
 
-    let o128x128 = root.join("128x128/apps/");
-    let png = o128x128.join(format!("{icon}.png"));
-    if png.is_file() {
-        return png.as_os_str().to_str().unwrap().to_string();
-    }
+        let icon_path = get_icon_path("org.example.App", &paths, &paths.share_dirs());
 
-    let o64x64 = root.join("64x64/apps/");
-    let png = o64x64.join(format!("{icon}.png"));
-    if png.is_file() {
-        return png.as_os_str().to_str().unwrap().to_string();
+        assert_eq!(
+            icon_folder.join("org.example.App.png").to_str().unwrap(),
+            icon_path
+        );
+        assert_eq!(
+            "",
+            get_icon_path("org.example.Missing", &paths, &paths.share_dirs())
+        );
     }
-
-    String::new()
 }
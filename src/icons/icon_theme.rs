@@ -0,0 +1,228 @@
+use crate::desktop_file::desktop_entry_group::DesktopEntryGroup;
+use crate::desktop_file::parse_groups;
+use std::path::{Path, PathBuf};
+
+const ICON_THEME_GROUP: &str = "Icon Theme";
+const FALLBACK_THEME: &str = "hicolor";
+
+/// A folder of an icon theme, like `48x48@2/apps`, with the size of its icons.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct IconDirectory {
+    pub path: String,
+    pub size: u32,
+    pub scale: u32,
+}
+
+impl IconDirectory {
+    /// The size of the icons in pixels.
+    pub(crate) fn pixels(&self) -> u32 {
+        self.size.saturating_mul(self.scale)
+    }
+
+    fn from_group(group: &DesktopEntryGroup) -> Option<Self> {
+        let number = |key: &str| group.get_string(key)?.trim().parse::<u32>().ok();
+        let size = number("Size")?;
+        let size = match group.get_string("Type").as_deref().map(str::trim) {
+            Some("Scalable") => number("MaxSize").unwrap_or(size),
+            _ => size,
+        };
+        Some(Self {
+            path: group.name.clone(),
+            size,
+            scale: number("Scale").unwrap_or(1),
+        })
+    }
+
+    /// Reads the size from a folder name like `256x256`, `256x256@2` or `256x256@2x`.
+    /// `scalable` folders count as 512 pixels.
+    fn from_folder_name(name: &str, context: &str) -> Option<Self> {
+        let path = format!("{name}/{context}");
+        if name == "scalable" {
+            return Some(Self {
+                path,
+                size: 512,
+                scale: 1,
+            });
+        }
+        let (size, scale) = match name.split_once('@') {
+            Some((size, scale)) => (size, scale.trim_end_matches('x').parse().ok()?),
+            None => (name, 1),
+        };
+        let (width, height) = size.split_once('x')?;
+        if width != height {
+            return None;
+        }
+        Some(Self {
+            path,
+            size: width.parse().ok()?,
+            scale,
+        })
+    }
+}
+
+/// An icon theme as described by its `index.theme`, with the folders that exist without
+/// being listed in it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct IconTheme {
+    pub name: String,
+    pub inherits: Vec<String>,
+    pub directories: Vec<IconDirectory>,
+}
+
+impl IconTheme {
+    /// Reads the `index.theme` of the first base folder that has one and adds the folders
+    /// of every base folder, so themes without an index, like the hicolor folders Flatpak
+    /// exports, are found as well. Returns `None` if no base folder has the theme.
+    pub(crate) fn load(name: &str, base_dirs: &[PathBuf]) -> Option<Self> {
+        let theme_dirs = base_dirs
+            .iter()
+            .map(|base_dir| base_dir.join(name))
+            .filter(|theme_dir| theme_dir.is_dir())
+            .collect::<Vec<PathBuf>>();
+        if theme_dirs.is_empty() {
+            return None;
+        }
+
+        let mut rtn = theme_dirs
+            .iter()
+            .find_map(|theme_dir| std::fs::read_to_string(theme_dir.join("index.theme")).ok())
+            .map(|content| Self::from_index(name, &content))
+            .unwrap_or_else(|| Self {
+                name: name.to_string(),
+                ..Default::default()
+            });
+
+        for theme_dir in &theme_dirs {
+            for (folder, context) in sub_folders(theme_dir) {
+                if let Some(directory) = IconDirectory::from_folder_name(&folder, &context)
+                    && !rtn.directories.iter().any(|d| d.path == directory.path)
+                {
+                    rtn.directories.push(directory);
+                }
+            }
+        }
+        Some(rtn)
+    }
+
+    pub(crate) fn from_index(name: &str, content: &str) -> Self {
+        let groups = parse_groups(content);
+        let list = |key: &str| {
+            groups
+                .iter()
+                .find(|g| g.name == ICON_THEME_GROUP)
+                .and_then(|g| g.get_string(key))
+                .map(|value| {
+                    value
+                        .split(',')
+                        .map(|item| item.trim().to_string())
+                        .filter(|item| !item.is_empty())
+                        .collect::<Vec<String>>()
+                })
+                .unwrap_or_default()
+        };
+        let mut folders = list("Directories");
+        folders.extend(list("ScaledDirectories"));
+
+        Self {
+            name: name.to_string(),
+            inherits: list("Inherits"),
+            directories: folders
+                .iter()
+                .filter_map(|folder| groups.iter().find(|g| &g.name == folder))
+                .filter_map(IconDirectory::from_group)
+                .collect(),
+        }
+    }
+}
+
+/// The theme and the themes it inherits from, breadth first, ending with `hicolor`.
+pub(crate) fn theme_chain(theme: Option<&str>, base_dirs: &[PathBuf]) -> Vec<IconTheme> {
+    let mut rtn: Vec<IconTheme> = Vec::new();
+    let mut names: Vec<String> = theme.into_iter().map(str::to_string).collect();
+    let mut next = 0;
+    while next < names.len() {
+        if let Some(loaded) = IconTheme::load(&names[next], base_dirs) {
+            for inherited in &loaded.inherits {
+                if !names.contains(inherited) {
+                    names.push(inherited.clone());
+                }
+            }
+            rtn.push(loaded);
+        }
+        next += 1;
+    }
+    if !names.iter().any(|name| name == FALLBACK_THEME)
+        && let Some(hicolor) = IconTheme::load(FALLBACK_THEME, base_dirs)
+    {
+        rtn.push(hicolor);
+    }
+    rtn
+}
+
+/// The `size/context` folder names two levels below the theme folder.
+fn sub_folders(theme_dir: &Path) -> Vec<(String, String)> {
+    let names = |dir: &Path| {
+        dir.read_dir()
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+            .collect::<Vec<String>>()
+    };
+    names(theme_dir)
+        .into_iter()
+        .flat_map(|folder| {
+            names(&theme_dir.join(&folder))
+                .into_iter()
+                .map(move |context| (folder.clone(), context))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::icons::icon_theme::{IconDirectory, IconTheme};
+
+    #[test]
+    fn test_parse_index_theme() {
+        let theme = IconTheme::from_index(
+            "Test",
+            r#"[Icon Theme]
+Name=Test
+Inherits=Parent, hicolor
+Directories=48x48/apps,scalable/apps,missing
+ScaledDirectories=48x48@2/apps
+
+[48x48/apps]
+Size=48
+Context=Applications
+Type=Fixed
+
+[48x48@2/apps]
+Size=48
+Scale=2
+
+[scalable/apps]
+Size=64
+MinSize=8
+MaxSize=1024
+Type=Scalable
+"#,
+        );
+        assert_eq!(vec!["Parent", "hicolor"], theme.inherits);
+        assert_eq!(
+            vec![48, 1024, 96],
+            theme
+                .directories
+                .iter()
+                .map(IconDirectory::pixels)
+                .collect::<Vec<u32>>()
+        );
+        assert_eq!(
+            Some(256),
+            IconDirectory::from_folder_name("128x128@2x", "apps").map(|d| d.pixels())
+        );
+        assert_eq!(None, IconDirectory::from_folder_name("symbolic", "apps"));
+    }
+}
@@ -0,0 +1,205 @@
+pub(crate) mod icon_theme;
+pub(crate) mod xpm;
+
+use crate::icons::icon_theme::{IconTheme, theme_chain};
+use crate::icons::xpm::read_xpm;
+use crate::utils::create_png_from_svg;
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
+
+/// Icon file types in the order they are preferred at the same size.
+const EXTENSIONS: [&str; 3] = ["png", "svg", "xpm"];
+
+/// Finds the icon the way the Icon Theme specification describes: in `theme` and the
+/// themes it inherits from, then `hicolor`, below the `icons` folder of every share folder,
+/// then in their `pixmaps` folders. Absolute paths are taken as they are. The largest icon
+/// of the first theme that has one wins, SVGs count as the largest. SVG and XPM icons are
+/// converted to PNG in the icon store because Steam can not show them. Returns an empty
+/// string if the icon is not found.
+pub(crate) fn find_icon(
+    icon: &str,
+    share_dirs: &[PathBuf],
+    icon_store: &Path,
+    theme: Option<&str>,
+) -> String {
+    let absolute = Path::new(icon);
+    if absolute.is_absolute() {
+        if !absolute.is_file() {
+            return String::new();
+        }
+        return to_steam_icon(absolute, icon_store).unwrap_or_default();
+    }
+    if icon.is_empty() || icon.contains('/') {
+        return String::new();
+    }
+
+    let name = EXTENSIONS
+        .iter()
+        .find_map(|extension| icon.strip_suffix(&format!(".{extension}")))
+        .unwrap_or(icon);
+    let base_dirs = share_dirs
+        .iter()
+        .map(|share_dir| share_dir.join("icons"))
+        .collect::<Vec<PathBuf>>();
+
+    theme_chain(theme, &base_dirs)
+        .iter()
+        .find_map(|theme| find_largest(name, theme, &base_dirs))
+        .or_else(|| {
+            share_dirs.iter().find_map(|share_dir| {
+                EXTENSIONS
+                    .iter()
+                    .map(|extension| {
+                        share_dir
+                            .join("pixmaps")
+                            .join(format!("{name}.{extension}"))
+                    })
+                    .find(|path| path.is_file())
+            })
+        })
+        .and_then(|path| to_steam_icon(&path, icon_store))
+        .unwrap_or_default()
+}
+
+/// The largest icon of the theme. Between icons of the same size unscaled folders win, then
+/// earlier base folders.
+fn find_largest(name: &str, theme: &IconTheme, base_dirs: &[PathBuf]) -> Option<PathBuf> {
+    let mut candidates = Vec::new();
+    for (base_index, base_dir) in base_dirs.iter().enumerate() {
+        for directory in &theme.directories {
+            for (extension_index, extension) in EXTENSIONS.iter().enumerate() {
+                let path = base_dir
+                    .join(&theme.name)
+                    .join(&directory.path)
+                    .join(format!("{name}.{extension}"));
+                if !path.is_file() {
+                    continue;
+                }
+                let pixels = match *extension {
+                    "svg" => u32::MAX,
+                    _ => directory.pixels(),
+                };
+                let rank = (
+                    pixels,
+                    directory.size,
+                    Reverse(extension_index),
+                    Reverse(base_index),
+                );
+                candidates.push((rank, path));
+            }
+        }
+    }
+    candidates
+        .into_iter()
+        .max_by_key(|(rank, _)| *rank)
+        .map(|(_, path)| path)
+}
+
+/// The path Steam can show, converting SVG and XPM icons to PNG.
+pub(crate) fn to_steam_icon(path: &Path, icon_store: &Path) -> Option<String> {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("svg") => convert_svg(path, icon_store),
+        Some("xpm") => convert_xpm(path, icon_store),
+        _ => Some(path.to_str()?.to_string()),
+    }
+}
+
+fn convert_svg(svg: &Path, icon_store: &Path) -> Option<String> {
+    std::fs::create_dir_all(icon_store).ok()?;
+    let rtn_path = icon_store.join(svg.file_stem()?).with_extension("png");
+
+    if let Err(err) = create_png_from_svg(svg, &rtn_path) {
+        println!("Could not convert the icon {} - {err}", svg.display());
+        return None;
+    }
+    Some(rtn_path.as_os_str().to_str()?.to_string())
+}
+
+fn convert_xpm(xpm: &Path, icon_store: &Path) -> Option<String> {
+    let image = match read_xpm(&std::fs::read_to_string(xpm).ok()?) {
+        Ok(value) => value,
+        Err(err) => {
+            println!("Could not read the icon {} - {err}", xpm.display());
+            return None;
+        }
+    };
+    std::fs::create_dir_all(icon_store).ok()?;
+    let rtn_path = icon_store.join(xpm.file_stem()?).with_extension("png");
+
+    image.save(&rtn_path).ok()?;
+    Some(rtn_path.as_os_str().to_str()?.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::icons::find_icon;
+    use crate::utils::TempDir;
+    use std::path::Path;
+
+    #[test]
+    fn test_find_icon() {
+        let dir = TempDir::new("icons");
+        let system = dir.join("usr/share");
+        let user = dir.join("home/.local/share");
+        let icon_store = dir.join("steam_icon_store");
+        let write = |path: &Path, content: &str| {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write(
+            &system.join("icons/Theme/index.theme"),
+            "[Icon Theme]\nInherits=Parent\nDirectories=apps/48\n\n[apps/48]\nSize=48\n",
+        );
+        write(
+            &system.join("icons/Parent/index.theme"),
+            "[Icon Theme]\nDirectories=32x32/apps\n\n[32x32/apps]\nSize=32\n",
+        );
+        let themed = system.join("icons/Theme/apps/48/themed.png");
+        let inherited = system.join("icons/Parent/32x32/apps/inherited.png");
+        let scaled = system.join("icons/hicolor/512x512@2/apps/app.png");
+        let user_icon = user.join("icons/hicolor/1024x1024/apps/user.png");
+        let pixmap = system.join("pixmaps/old.png");
+        for path in [&themed, &inherited, &scaled, &user_icon, &pixmap] {
+            write(path, "png");
+        }
+        write(&system.join("icons/hicolor/48x48/apps/themed.png"), "png");
+        write(&system.join("icons/hicolor/512x512/apps/app.png"), "png");
+        write(&system.join("icons/hicolor/96x96/apps/app.png"), "png");
+        write(&system.join("icons/hicolor/128x128/apps/user.png"), "png");
+        write(&system.join("pixmaps/broken.svg"), "not an svg");
+        write(
+            &system.join("pixmaps/xpm.xpm"),
+            "\"1 1 1 1\",\n\"x c #00FF00\",\n\"x\"",
+        );
+        let share_dirs = vec![user.clone(), system.clone()];
+        let find = |icon: &str, theme| find_icon(icon, &share_dirs, &icon_store, theme);
+
+        assert_eq!(themed.to_str().unwrap(), find("themed", Some("Theme")));
+        assert_eq!(
+            inherited.to_str().unwrap(),
+            find("inherited", Some("Theme"))
+        );
+        assert_eq!(
+            system
+                .join("icons/hicolor/48x48/apps/themed.png")
+                .to_str()
+                .unwrap(),
+            find("themed", None)
+        );
+        assert_eq!(scaled.to_str().unwrap(), find("app", Some("Theme")));
+        assert_eq!(user_icon.to_str().unwrap(), find("user.png", None));
+        assert_eq!(pixmap.to_str().unwrap(), find("old", None));
+        assert_eq!(
+            pixmap.to_str().unwrap(),
+            find(pixmap.to_str().unwrap(), None)
+        );
+        assert_eq!(
+            icon_store.join("xpm.png").to_str().unwrap(),
+            find("xpm", None)
+        );
+        assert!(image::open(icon_store.join("xpm.png")).is_ok());
+        assert_eq!("", find("broken", None));
+        assert_eq!("", find("missing", Some("Theme")));
+        assert_eq!("", find("", None));
+    }
+}
@@ -0,0 +1,185 @@
+use anyhow::anyhow;
+use image::{Rgba, RgbaImage};
+
+/// Colors X11 knows by name that appear in XPM icons. Other names are drawn black.
+const COLOR_NAMES: [(&str, [u8; 3]); 10] = [
+    ("black", [0, 0, 0]),
+    ("white", [255, 255, 255]),
+    ("red", [255, 0, 0]),
+    ("green", [0, 255, 0]),
+    ("blue", [0, 0, 255]),
+    ("yellow", [255, 255, 0]),
+    ("cyan", [0, 255, 255]),
+    ("magenta", [255, 0, 255]),
+    ("gray", [190, 190, 190]),
+    ("grey", [190, 190, 190]),
+];
+
+/// Keys that start a color of a visual in an XPM color line.
+const COLOR_KEYS: [&str; 5] = ["c", "m", "g", "g4", "s"];
+
+/// Larger images are rejected before their pixels are allocated.
+const MAX_SIZE: u32 = 1024;
+
+/// Reads an XPM 3 image, the format of many icons in `/usr/share/pixmaps`, which Steam
+/// can not show.
+pub(crate) fn read_xpm(content: &str) -> anyhow::Result<RgbaImage> {
+    let mut strings = quoted_strings(content).into_iter();
+    let header = strings.next().ok_or(anyhow!("The XPM has no values"))?;
+    let values = header
+        .split_whitespace()
+        .take(4)
+        .map(|value| value.parse::<u32>())
+        .collect::<Result<Vec<u32>, _>>()?;
+    let [width, height, colors, chars_per_pixel] = values[..] else {
+        return Err(anyhow!("Invalid XPM values {header}"));
+    };
+    if width == 0 || height == 0 || width > MAX_SIZE || height > MAX_SIZE {
+        return Err(anyhow!(
+            "The XPM is {width}x{height}, only sizes up to {MAX_SIZE}x{MAX_SIZE} are supported"
+        ));
+    }
+    if chars_per_pixel == 0 {
+        return Err(anyhow!("Invalid XPM values {header}"));
+    }
+    let chars_per_pixel = chars_per_pixel as usize;
+
+    let mut palette: Vec<(String, Rgba<u8>)> = Vec::new();
+    for _ in 0..colors {
+        let line = strings.next().ok_or(anyhow!("The XPM misses colors"))?;
+        let key: String = line.chars().take(chars_per_pixel).collect();
+        let rest: String = line.chars().skip(chars_per_pixel).collect();
+        palette.push((key, parse_color(&rest)));
+    }
+
+    let rows = strings
+        .take_while(|row| !row.starts_with("XPMEXT"))
+        .map(|row| row.chars().collect::<Vec<char>>())
+        .collect::<Vec<Vec<char>>>();
+    if rows.len() != height as usize {
+        return Err(anyhow!(
+            "The XPM has {} pixel rows instead of {height}",
+            rows.len()
+        ));
+    }
+    if let Some(y) = rows
+        .iter()
+        .position(|row| row.len() != width as usize * chars_per_pixel)
+    {
+        return Err(anyhow!("The XPM row {y} does not have {width} pixels"));
+    }
+
+    let mut rtn = RgbaImage::new(width, height);
+    for (y, row) in rows.iter().enumerate() {
+        for x in 0..width {
+            let start = x as usize * chars_per_pixel;
+            let key: String = row[start..start + chars_per_pixel].iter().collect();
+            let color = palette
+                .iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, color)| *color)
+                .ok_or(anyhow!("The XPM has no color {key}"))?;
+            rtn.put_pixel(x, y as u32, color);
+        }
+    }
+    Ok(rtn)
+}
+
+/// The C string literals of the file, which hold the values, colors and pixels.
+fn quoted_strings(content: &str) -> Vec<String> {
+    let mut rtn = Vec::new();
+    let mut rest = content;
+    while let Some(start) = rest.find('"') {
+        let Some(length) = rest[start + 1..].find('"') else {
+            break;
+        };
+        rtn.push(rest[start + 1..start + 1 + length].to_string());
+        rest = &rest[start + 2 + length..];
+    }
+    rtn
+}
+
+/// The color of the color visual `c` of a color line, else of the first visual.
+fn parse_color(definition: &str) -> Rgba<u8> {
+    let tokens = definition.split_whitespace().collect::<Vec<&str>>();
+    let mut visuals: Vec<(&str, String)> = Vec::new();
+    for token in tokens {
+        match visuals.last_mut() {
+            Some((_, color)) if !COLOR_KEYS.contains(&token) || color.is_empty() => {
+                if !color.is_empty() {
+                    color.push(' ');
+                }
+                color.push_str(token);
+            }
+            _ => visuals.push((token, String::new())),
+        }
+    }
+    let color = visuals
+        .iter()
+        .find(|(key, _)| *key == "c")
+        .or(visuals.first())
+        .map(|(_, color)| color.to_lowercase())
+        .unwrap_or_default();
+
+    if color == "none" {
+        return Rgba([0, 0, 0, 0]);
+    }
+    if let Some(hex) = color.strip_prefix('#') {
+        let digits = hex.len() / 3;
+        if (1..=4).contains(&digits) && hex.len() % 3 == 0 && hex.is_ascii() {
+            let channel = |i: usize| {
+                let value = u32::from_str_radix(&hex[i * digits..(i + 1) * digits], 16).ok()?;
+                let max = (1u32 << (4 * digits)) - 1;
+                Some((value * 255 / max) as u8)
+            };
+            if let (Some(r), Some(g), Some(b)) = (channel(0), channel(1), channel(2)) {
+                return Rgba([r, g, b, 255]);
+            }
+        }
+    }
+    let [r, g, b] = COLOR_NAMES
+        .iter()
+        .find(|(name, _)| *name == color)
+        .map_or([0, 0, 0], |(_, rgb)| *rgb);
+    Rgba([r, g, b, 255])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::icons::xpm::read_xpm;
+    use image::Rgba;
+
+    #[test]
+    fn test_read_xpm() {
+        let image = read_xpm(
+            r#"/* XPM */
+static char * icon_xpm[] = {
+"3 2 4 2",
+"  c None",
+".. c #FF0000",
+"++ s mask c #00000000FFFF",
+"@@ c white",
+"  ..++",
+"@@..  "};
+"#,
+        )
+        .unwrap();
+
+        assert_eq!((3, 2), image.dimensions());
+        assert_eq!(Rgba([0, 0, 0, 0]), *image.get_pixel(0, 0));
+        assert_eq!(Rgba([255, 0, 0, 255]), *image.get_pixel(1, 0));
+        assert_eq!(Rgba([0, 0, 255, 255]), *image.get_pixel(2, 0));
+        assert_eq!(Rgba([255, 255, 255, 255]), *image.get_pixel(0, 1));
+        assert!(read_xpm("\"2 1 1 1\", \"x c black\", \"x\"").is_err());
+    }
+
+    #[test]
+    fn test_reject_invalid_xpm() {
+        assert!(read_xpm("\"100000 100000 1 1\", \"x c black\", \"x\"").is_err());
+        assert!(read_xpm("\"1 2 1 1\", \"x c black\", \"x\"").is_err());
+        assert!(read_xpm("\"1 1 1 1\", \"x c black\", \"x\", \"x\"").is_err());
+        assert!(read_xpm("\"1 1 1 1\", \"x c black\", \"xx\"").is_err());
+        let image = read_xpm("\"1 1 1 1\", \"x c #FFFFFFFFFFFFFFFFFFFFFFFF\", \"x\"").unwrap();
+        assert_eq!(Rgba([0, 0, 0, 255]), *image.get_pixel(0, 0));
+    }
+}
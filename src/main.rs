@@ -1,6 +1,12 @@
+use crate::actions::add_desktop_file::add_desktop_file;
+use crate::actions::add_shortcut::add_shortcut;
+use crate::actions::edit_shortcut::edit_shortcut;
 use crate::actions::flatpak::{add_flatpak, fix_flatpak};
+use crate::actions::list_users::list_users;
 use crate::actions::minus_games::add_minus_games_game_to_steam;
 use crate::actions::print_shortcuts::{print_only_shortcuts, print_shortcuts};
+use crate::actions::remove_shortcut::remove_shortcut;
+use crate::actions::snapshots::{backup, restore};
 use crate::config::{Actions, Config};
 use clap::Parser;
 
@@ -8,32 +14,88 @@ mod actions;
 mod config;
 mod desktop_file;
 mod flatpak;
+mod icons;
 mod pc_gaming_wiki;
 mod shortcuts;
+mod snapshots;
 mod steam;
 mod steamstatic;
 mod utils;
+mod vdf;
 
 fn main() {
     dotenvy::dotenv().ok();
     let config = Config::parse();
 
-    match &config.get_action() {
+    if config.get_action().is_mutating() && !config.ensure_steam_is_not_running() {
+        return;
+    }
+
+    if !config.all_users {
+        run_action(&config);
+        return;
+    }
+
+    if !config.get_action().supports_all_users() {
+        println!("{} does not support --all-users", config.get_action());
+        return;
+    }
+
+    let user_configs = match config.get_all_user_configs() {
+        Ok(value) => value,
+        Err(err) => {
+            println!("Failed to prepare the Steam users - {err}");
+            return;
+        }
+    };
+
+    let mut results = Vec::with_capacity(user_configs.len());
+    for (user_id, user_config) in user_configs {
+        println!("User {user_id}:");
+        results.push((user_id, run_action(&user_config)));
+    }
+
+    println!("Results:");
+    for (user_id, success) in results {
+        println!("  {user_id}: {}", if success { "ok" } else { "failed" });
+    }
+}
+
+fn run_action(config: &Config) -> bool {
+    match config.get_action() {
         Actions::PrintShortcuts => {
-            print_shortcuts(&config);
+            print_shortcuts(config);
         }
         Actions::PrintOnlyShortcuts => {
-            print_only_shortcuts(&config);
+            print_only_shortcuts(config);
         }
+        Actions::ListUsers(list_users_config) => list_users(config, list_users_config),
         #[cfg(not(target_family = "windows"))]
         Actions::AddMinusGamesGameToSteam(sub_config) => {
-            add_minus_games_game_to_steam(&config, sub_config);
+            return add_minus_games_game_to_steam(config, sub_config);
         }
         #[cfg(not(target_family = "windows"))]
-        Actions::AddFlatpak(add_flatpak_config) => add_flatpak(&config, add_flatpak_config),
+        Actions::AddFlatpak(add_flatpak_config) => return add_flatpak(config, add_flatpak_config),
+        #[cfg(not(target_family = "windows"))]
+        Actions::AddDesktopFile(add_desktop_file_config) => {
+            return add_desktop_file(config, add_desktop_file_config);
+        }
+        #[cfg(not(target_family = "windows"))]
+        Actions::AddShortcut(add_shortcut_config) => {
+            return add_shortcut(config, add_shortcut_config);
+        }
         #[cfg(not(target_family = "windows"))]
         Actions::FixFlatpak(fix_flatpak_config) => {
-            fix_flatpak(&config, fix_flatpak_config.force);
+            return fix_flatpak(config, fix_flatpak_config.force);
+        }
+        Actions::EditShortcut(edit_shortcut_config) => {
+            return edit_shortcut(config, edit_shortcut_config);
+        }
+        Actions::RemoveShortcut(remove_shortcut_config) => {
+            return remove_shortcut(config, remove_shortcut_config);
         }
+        Actions::Backup(backup_config) => backup(config, backup_config),
+        Actions::Restore(restore_config) => restore(config, restore_config),
     }
+    true
 }
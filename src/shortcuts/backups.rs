@@ -0,0 +1,69 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Number of backups that are kept next to a shortcuts file.
+pub(crate) const MAX_BACKUPS: usize = 5;
+
+const BACKUP_SUFFIX: &str = ".bak";
+
+/// Copies the file to `{file_name}.{unix_millis}.bak` and removes the oldest backups
+/// so that at most [`MAX_BACKUPS`] remain. Failing to remove them is only reported.
+pub(crate) fn create_backup(path: &Path) -> std::io::Result<PathBuf> {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let mut backup_name = backup_prefix(path);
+    backup_name.push(format!("{millis}{BACKUP_SUFFIX}"));
+    let backup_path = path.with_file_name(backup_name);
+
+    std::fs::copy(path, &backup_path)?;
+    if let Err(err) = rotate_backups(path) {
+        println!("Failed to remove old backups of {} - {err}", path.display());
+    }
+
+    Ok(backup_path)
+}
+
+/// All backups of the file, oldest first.
+pub(crate) fn list_backups(path: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let Some(parent) = path.parent() else {
+        return Ok(Vec::new());
+    };
+    let prefix = backup_prefix(path);
+    let prefix = prefix.to_string_lossy();
+
+    let mut rtn: Vec<(u128, PathBuf)> = Vec::new();
+    for dir_entry in std::fs::read_dir(parent)? {
+        let dir_entry = dir_entry?;
+        let file_name = dir_entry.file_name();
+        let Some(millis) = file_name
+            .to_str()
+            .and_then(|name| name.strip_prefix(prefix.as_ref()))
+            .and_then(|name| name.strip_suffix(BACKUP_SUFFIX))
+            .and_then(|millis| millis.parse().ok())
+        else {
+            continue;
+        };
+        rtn.push((millis, dir_entry.path()));
+    }
+    rtn.sort();
+
+    Ok(rtn.into_iter().map(|(_, path)| path).collect())
+}
+
+fn rotate_backups(path: &Path) -> std::io::Result<()> {
+    let backups = list_backups(path)?;
+    let obsolete = backups.len().saturating_sub(MAX_BACKUPS);
+    for backup in &backups[..obsolete] {
+        std::fs::remove_file(backup)?;
+    }
+    Ok(())
+}
+
+fn backup_prefix(path: &Path) -> OsString {
+    let mut rtn = path.file_name().unwrap_or_default().to_os_string();
+    rtn.push(".");
+    rtn
+}
@@ -1,19 +1,25 @@
+pub(crate) mod backups;
 pub(crate) mod parsing_error;
+pub(crate) mod selector;
 pub(crate) mod shortcut;
 
+use crate::config::RootMapping;
 use crate::desktop_file::DesktopFile;
-use crate::flatpak::get_icon_path;
+use crate::flatpak::{FlatpakPaths, get_icon_path};
+use crate::shortcuts::backups::create_backup;
 use crate::shortcuts::parsing_error::ParsingError;
+use crate::shortcuts::selector::ShortcutSelector;
 use crate::shortcuts::shortcut::ShortcutEntry;
-use crate::utils::{
-    add_integer_to_shortcut, add_string_to_shortcut, create_grid_for_shortcut,
-    insert_entry_string_integer, insert_entry_string_map, insert_entry_string_string,
-    insert_str_into_bytes, insert_string_into_bytes, try_read_integer, try_read_string,
-};
+use crate::utils::create_grid_for_shortcut;
+use crate::vdf;
+use crate::vdf::binary::node::{Entry, Node};
+use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
 use std::fmt::{Display, Formatter};
-use std::ops::AddAssign;
-use std::path::Path;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Shortcuts {
@@ -27,9 +33,41 @@ impl Display for Shortcuts {
 }
 
 impl Shortcuts {
-    pub(crate) fn insert_shortcut(&mut self, shortcut_entry: ShortcutEntry) {
-        self.shortcuts.retain(|i| i.appid != shortcut_entry.appid);
-        self.shortcuts.push(shortcut_entry);
+    /// The shortcut [`Shortcuts::insert_shortcut`] replaces: the one with the same appid,
+    /// else the first one [`ShortcutEntry::is_same_shortcut`] holds for.
+    pub(crate) fn find_existing(&self, shortcut_entry: &ShortcutEntry) -> Option<usize> {
+        self.shortcuts
+            .iter()
+            .position(|s| s.appid == shortcut_entry.appid)
+            .or_else(|| {
+                self.shortcuts
+                    .iter()
+                    .position(|s| s.is_same_shortcut(shortcut_entry))
+            })
+    }
+
+    /// Replaces the existing shortcut where it is, keeping its appid so that its artwork
+    /// still applies, or adds the shortcut. Returns the appid the shortcut is stored with.
+    pub(crate) fn insert_shortcut(&mut self, mut shortcut_entry: ShortcutEntry) -> u32 {
+        let Some(index) = self.find_existing(&shortcut_entry) else {
+            let rtn = shortcut_entry.appid;
+            self.shortcuts.push(shortcut_entry);
+            return rtn;
+        };
+        let existing = &self.shortcuts[index];
+        shortcut_entry.appid = existing.appid;
+        shortcut_entry.last_play_time = existing.last_play_time;
+        self.shortcuts[index] = shortcut_entry;
+        self.shortcuts[index].appid
+    }
+
+    /// Removes and returns every shortcut the selector matches.
+    pub(crate) fn remove_shortcuts(&mut self, selector: &ShortcutSelector) -> Vec<ShortcutEntry> {
+        let (rtn, kept) = std::mem::take(&mut self.shortcuts)
+            .into_iter()
+            .partition(|shortcut| selector.matches(shortcut));
+        self.shortcuts = kept;
+        rtn
     }
 
     pub(crate) fn from_path(path: &Path) -> Option<Self> {
@@ -37,12 +75,35 @@ impl Shortcuts {
         Self::try_from_bytes(&bytes).ok()
     }
 
-    pub(crate) fn save_to(&self, shortcuts_file_path: &Path) {
+    /// Writes the shortcuts to a temporary file next to `shortcuts_file_path`, verifies that
+    /// it parses back to the same content and atomically replaces the original with it.
+    /// The previous file is kept as a backup. The temporary file is removed on any error.
+    pub(crate) fn save_to(&self, shortcuts_file_path: &Path) -> anyhow::Result<()> {
         let bytes = self.to_bytes();
-        std::fs::write(shortcuts_file_path, bytes).unwrap()
+
+        let mut tmp_file_name = OsString::from(".");
+        tmp_file_name.push(shortcuts_file_path.file_name().unwrap_or_default());
+        tmp_file_name.push(format!(".{}.tmp", std::process::id()));
+        let tmp_file_path = shortcuts_file_path.with_file_name(tmp_file_name);
+
+        let result = write_and_verify(&tmp_file_path, &bytes)
+            .and_then(|_| replace_with(shortcuts_file_path, &tmp_file_path));
+        if result.is_err() {
+            std::fs::remove_file(&tmp_file_path).ok();
+        }
+        result
     }
 
-    pub(crate) fn fix_flatpaks(&mut self, grid_path: &Path, force: bool) -> bool {
+    /// Looks up the icons of Flatpak shortcuts without one in `data_dirs` and generates their
+    /// artwork. `roots` maps the stored paths to this system and back.
+    pub(crate) fn fix_flatpaks(
+        &mut self,
+        grid_path: &Path,
+        force: bool,
+        flatpak_paths: &FlatpakPaths,
+        data_dirs: &[PathBuf],
+        roots: &RootMapping,
+    ) -> bool {
         let mut has_changes = false;
         for shortcut in self.shortcuts.iter_mut() {
             if !force && (!shortcut.icon.is_empty() || !shortcut.exe.contains("flatpak")) {
@@ -53,12 +114,15 @@ impl Shortcuts {
 
             if !shortcut.shortcut_path.is_empty() {
                 if let Some(desktop_file) =
-                    DesktopFile::try_from_path(Path::new(&shortcut.shortcut_path))
+                    DesktopFile::try_from_path(Path::new(&roots.to_local(&shortcut.shortcut_path)))
                 {
-                    let path = get_icon_path(&desktop_file.icon);
+                    let path = get_icon_path(&desktop_file.icon, flatpak_paths, data_dirs);
                     if !path.is_empty() {
                         shortcut.icon = path;
-                        create_grid_for_shortcut(shortcut, grid_path);
+                        if let Err(err) = create_grid_for_shortcut(shortcut, grid_path) {
+                            println!("No artwork for {} - {err}", shortcut.app_name);
+                        }
+                        shortcut.icon = roots.to_target(&shortcut.icon);
                         shortcut.flatpak_app_id = desktop_file.icon;
                         has_changes = true;
                         println!("Changed Shortcut: {}", shortcut.app_name);
@@ -76,148 +140,461 @@ impl Shortcuts {
     }
 
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut rtn = Vec::new();
-
-        rtn.push(0x00);
-        insert_str_into_bytes(&mut rtn, "shortcuts");
-
-        for (i, shortcut) in self.shortcuts.iter().enumerate() {
-            rtn.push(0x00);
-            insert_string_into_bytes(&mut rtn, i.to_string());
-            insert_entry_string_integer(&mut rtn, "appid", shortcut.appid);
-            insert_entry_string_string(&mut rtn, "AppName", shortcut.app_name.as_str());
-            insert_entry_string_string(&mut rtn, "Exe", shortcut.exe.as_str());
-            insert_entry_string_string(&mut rtn, "StartDir", shortcut.start_dir.as_str());
-            insert_entry_string_string(&mut rtn, "icon", shortcut.icon.as_str());
-            insert_entry_string_string(&mut rtn, "ShortcutPath", shortcut.shortcut_path.as_str());
-            insert_entry_string_string(&mut rtn, "LaunchOptions", shortcut.launch_options.as_str());
-            insert_entry_string_integer(&mut rtn, "IsHidden", shortcut.is_hidden);
-            insert_entry_string_integer(
-                &mut rtn,
-                "AllowDesktopConfig",
-                shortcut.allow_desktop_config,
-            );
-            insert_entry_string_integer(&mut rtn, "AllowOverlay", shortcut.allow_overlay);
-            insert_entry_string_integer(&mut rtn, "OpenVR", shortcut.open_vr);
-            insert_entry_string_integer(&mut rtn, "Devkit", shortcut.devkit);
-            insert_entry_string_string(&mut rtn, "DevkitGameID", shortcut.devkit_game_id.as_str());
-            insert_entry_string_integer(
-                &mut rtn,
-                "DevkitOverrideAppID",
-                shortcut.devkit_override_app_id,
-            );
-            insert_entry_string_integer(&mut rtn, "LastPlayTime", shortcut.last_play_time);
-            insert_entry_string_string(&mut rtn, "FlatpakAppID", shortcut.flatpak_app_id.as_str());
-            insert_entry_string_string(&mut rtn, "sortas", shortcut.sort_as.as_str());
-            insert_entry_string_map(&mut rtn, "tags");
-            rtn.push(0x08);
-        }
-        rtn.push(0x08);
-        rtn.push(0x08);
-        rtn
+        let shortcuts = self
+            .shortcuts
+            .iter()
+            .enumerate()
+            .map(|(i, shortcut)| Entry::new(&i.to_string(), Node::Map(shortcut.to_entries())))
+            .collect();
+
+        vdf::binary::to_bytes(&[Entry::new(SHORTCUTS_STRING, Node::Map(shortcuts))])
     }
 
     pub fn try_from_bytes(bytes: &[u8]) -> Result<Shortcuts, ParsingError> {
-        let mut parsing_state = ParsingState::Start;
         let mut rtn = Shortcuts::default();
-        let mut current_shortcut = None;
-
-        let mut position = 0;
-        let mut shortcuts_position = 0;
-
-        while position < bytes.len() {
-            match parsing_state {
-                ParsingState::Start => {
-                    if bytes[position] != 0x00 {
-                        return Err(ParsingError::WrongStartingByte);
-                    }
-                    parsing_state = ParsingState::Shortcuts;
-                    position = 1;
-                }
-                ParsingState::Shortcuts => {
-                    let substring = &bytes[position..position + SHORTCUTS_STRING.len()];
-                    if substring != SHORTCUTS_STRING.as_bytes() {
-                        return Err(ParsingError::ShortcutsNotFound);
-                    }
-                    position = 3 + SHORTCUTS_STRING.len();
-                    parsing_state = ParsingState::Shortcut;
-                }
-                ParsingState::Shortcut => {
-                    if bytes[position] == 0x08 {
-                        position.add_assign(1);
-                        continue;
-                    }
 
-                    let result = try_read_string(&mut position, bytes)?;
-                    if result != shortcuts_position.to_string() {
-                        return Err(ParsingError::ShortcutsEntriesDoNotAlign);
-                    }
-                    shortcuts_position += 1;
-                    parsing_state = ParsingState::Entry;
-                    current_shortcut = Some(ShortcutEntry::default())
+        let root = vdf::binary::from_bytes(bytes)?;
+        let mut offset = 0;
+        let mut shortcuts_option = None;
+        for entry in root {
+            let header_len = vdf::binary::header_len(&entry);
+            let encoded_len = vdf::binary::encoded_len(&entry);
+            match entry {
+                Entry {
+                    key,
+                    value: Node::Map(shortcuts),
+                } if key == SHORTCUTS_STRING => {
+                    offset += header_len;
+                    shortcuts_option = Some(shortcuts);
+                    break;
                 }
+                _ => offset += encoded_len,
+            }
+        }
+        let Some(shortcuts) = shortcuts_option else {
+            return Err(ParsingError::ShortcutsNotFound { offset });
+        };
 
-                ParsingState::Entry => {
-                    match bytes[position] {
-                        0x00 => {
-                            parsing_state = ParsingState::StringMap;
-                        }
-                        0x01 => {
-                            parsing_state = ParsingState::StringString;
-                        }
-                        0x02 => {
-                            parsing_state = ParsingState::StringInteger;
-                        }
-                        0x08 => {
-                            let shortcut_entry = current_shortcut
-                                .take()
-                                .ok_or(ParsingError::CurrentShortcutEntryIsEmpty)?;
-
-                            rtn.shortcuts.push(shortcut_entry);
-                            position.add_assign(1);
-                            parsing_state = ParsingState::Shortcut;
-                        }
-                        _ => return Err(ParsingError::InvalidMapType(bytes[position])),
-                    }
-                    position += 1;
-                }
-                ParsingState::StringString => {
-                    let key = try_read_string(&mut position, bytes)?;
-                    let value = try_read_string(&mut position, bytes)?;
-                    add_string_to_shortcut(current_shortcut.as_mut(), key, value)?;
-                    parsing_state = ParsingState::Entry;
-                }
-                ParsingState::StringInteger => {
-                    let key = try_read_string(&mut position, bytes)?;
-                    let value = try_read_integer(&mut position, bytes)?;
-                    add_integer_to_shortcut(current_shortcut.as_mut(), key, value)?;
-                    parsing_state = ParsingState::Entry;
-                }
-                ParsingState::StringMap => {
-                    while position < bytes.len() {
-                        if bytes[position] == 0x08 {
-                            position.add_assign(1);
-                            break;
-                        }
-                        position.add_assign(1);
-                    }
-                    parsing_state = ParsingState::Entry;
-                }
+        for (index, entry) in shortcuts.into_iter().enumerate() {
+            let next_offset = offset + vdf::binary::encoded_len(&entry);
+            if entry.key != index.to_string() {
+                return Err(ParsingError::ShortcutsEntriesDoNotAlign {
+                    offset,
+                    index,
+                    key: entry.key,
+                });
             }
+            let Node::Map(entries) = entry.value else {
+                return Err(ParsingError::ShortcutIsNotAMap { offset, index });
+            };
+            rtn.shortcuts.push(ShortcutEntry::from_entries(entries));
+            offset = next_offset;
         }
 
         Ok(rtn)
     }
 }
 
-enum ParsingState {
-    Start,
-    Shortcuts,
-    Shortcut,
-    Entry,
-    StringString,
-    StringInteger,
-    StringMap,
+/// Backs up the shortcuts file and moves the temporary file in its place.
+fn replace_with(shortcuts_file_path: &Path, tmp_file_path: &Path) -> anyhow::Result<()> {
+    if shortcuts_file_path.is_file() {
+        create_backup(shortcuts_file_path)?;
+    }
+    std::fs::rename(tmp_file_path, shortcuts_file_path)?;
+
+    #[cfg(target_family = "unix")]
+    if let Some(parent) = shortcuts_file_path.parent() {
+        File::open(parent)?.sync_all()?;
+    }
+
+    Ok(())
+}
+
+fn write_and_verify(path: &Path, bytes: &[u8]) -> anyhow::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+
+    let written = std::fs::read(path)?;
+    let parsed = Shortcuts::try_from_bytes(&written)
+        .map_err(|err| anyhow!("Written shortcuts file can not be parsed - {err}"))?;
+    if written != bytes || parsed.to_bytes() != bytes {
+        return Err(anyhow!(
+            "Written shortcuts file does not match the shortcuts"
+        ));
+    }
+
+    Ok(())
 }
 
 const SHORTCUTS_STRING: &str = "shortcuts";
+
+#[cfg(test)]
+mod tests {
+    use crate::shortcuts::Shortcuts;
+    use crate::shortcuts::backups::{MAX_BACKUPS, list_backups};
+    use crate::shortcuts::parsing_error::ParsingError;
+    use crate::shortcuts::selector::ShortcutSelector;
+    use crate::shortcuts::shortcut::ShortcutEntry;
+    use crate::utils::{TempDir, generate_app_id, generate_steam_app_id};
+    use crate::vdf;
+    use crate::vdf::binary::node::{Entry, Node};
+
+    #[test]
+    fn test_unknown_keys_round_trip() {
+        let extra_fields = vec![
+            Entry::new("NewStringKey", Node::String("value".to_string())),
+            Entry::new("NewIntegerKey", Node::Int32(42)),
+            Entry::new(
+                "NewMapKey",
+                Node::Map(vec![Entry::new("0", Node::String("nested".to_string()))]),
+            ),
+            Entry::new("NewUInt64Key", Node::UInt64(u64::MAX)),
+        ];
+        let shortcuts = Shortcuts {
+            shortcuts: vec![ShortcutEntry {
+                appid: 1,
+                app_name: "Test".to_string(),
+                extra_fields: extra_fields.clone(),
+                ..Default::default()
+            }],
+        };
+
+        let bytes = shortcuts.to_bytes();
+        let parsed = Shortcuts::try_from_bytes(&bytes).unwrap();
+
+        assert_eq!(extra_fields, parsed.shortcuts[0].extra_fields);
+        assert_eq!(bytes, parsed.to_bytes());
+    }
+
+    #[test]
+    fn test_unknown_keys_keep_their_position() {
+        let string = |value: &str| Node::String(value.to_string());
+        let mut entries = ShortcutEntry::default().to_entries();
+        entries.insert(2, Entry::new("NewStringKey", string("between")));
+        entries.insert(0, Entry::new("NewIntegerKey", Node::Int32(7)));
+        let bytes = vdf::binary::to_bytes(&[Entry::new(
+            "shortcuts",
+            Node::Map(vec![Entry::new("0", Node::Map(entries))]),
+        )]);
+
+        let mut parsed = Shortcuts::try_from_bytes(&bytes).unwrap();
+        assert_eq!(bytes, parsed.to_bytes());
+
+        parsed.shortcuts[0].app_name = "Renamed".to_string();
+        let keys = parsed.shortcuts[0]
+            .to_entries()
+            .into_iter()
+            .map(|e| e.key)
+            .take(4)
+            .collect::<Vec<String>>();
+        assert_eq!(
+            vec!["NewIntegerKey", "appid", "AppName", "NewStringKey"],
+            keys
+        );
+    }
+
+    #[test]
+    fn test_remove_shortcuts() {
+        let mut shortcuts = Shortcuts::default();
+        for (appid, app_name) in [(1, "Keep"), (2, "Remove"), (3, "Remove")] {
+            shortcuts.shortcuts.push(ShortcutEntry {
+                appid,
+                app_name: app_name.to_string(),
+                ..Default::default()
+            });
+        }
+
+        let removed = shortcuts.remove_shortcuts(&ShortcutSelector {
+            name: Some("Remove".to_string()),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            vec![2, 3],
+            removed.iter().map(|s| s.appid).collect::<Vec<u32>>()
+        );
+        assert_eq!(1, shortcuts.shortcuts.len());
+        assert_eq!("Keep", shortcuts.shortcuts[0].app_name);
+    }
+
+    #[test]
+    fn test_set_field() {
+        let mut shortcut = ShortcutEntry {
+            appid: 1,
+            app_name: "Test".to_string(),
+            extra_fields: vec![Entry::new("NewIntegerKey", Node::Int32(1))],
+            ..Default::default()
+        };
+
+        shortcut.set_field("LaunchOptions", "-fullscreen").unwrap();
+        shortcut.set_field("is_hidden", "true").unwrap();
+        shortcut.set_field("SORT_AS", "Sorted").unwrap();
+        shortcut.set_field("appid", "4201274167").unwrap();
+        shortcut.set_field("tags", "Favorites, Emulators").unwrap();
+        shortcut.set_field("newintegerkey", "42").unwrap();
+        shortcut.set_field("NewStringKey", "value").unwrap();
+
+        assert_eq!("-fullscreen", shortcut.launch_options);
+        assert_eq!(1, shortcut.is_hidden);
+        assert_eq!("Sorted", shortcut.sort_as);
+        assert_eq!(4201274167, shortcut.appid);
+        assert_eq!(vec!["Favorites", "Emulators"], shortcut.tags);
+        assert_eq!(
+            vec![
+                Entry::new("NewIntegerKey", Node::Int32(42)),
+                Entry::new("NewStringKey", Node::String("value".to_string())),
+            ],
+            shortcut.extra_fields
+        );
+        assert!(shortcut.set_field("IsHidden", "yes").is_err());
+
+        shortcut.unset_field("NewStringKey").unwrap();
+        assert_eq!(1, shortcut.extra_fields.len());
+        assert!(shortcut.unset_field("AppName").is_err());
+        assert!(shortcut.unset_field("Missing").is_err());
+    }
+
+    #[test]
+    fn test_tags_round_trip() {
+        let tags = vec!["Favorites".to_string(), "Emulators".to_string()];
+        let shortcuts = Shortcuts {
+            shortcuts: vec![ShortcutEntry {
+                appid: 1,
+                app_name: "Test".to_string(),
+                tags: tags.clone(),
+                ..Default::default()
+            }],
+        };
+
+        let bytes = shortcuts.to_bytes();
+        let parsed = Shortcuts::try_from_bytes(&bytes).unwrap();
+
+        assert_eq!(tags, parsed.shortcuts[0].tags);
+        assert!(parsed.shortcuts[0].extra_fields.is_empty());
+        assert_eq!(bytes, parsed.to_bytes());
+    }
+
+    #[test]
+    fn test_insert_updates_the_same_shortcut() {
+        let mut shortcuts = example_shortcuts();
+        let legacy = ShortcutEntry {
+            appid: generate_app_id("/usr/bin/flatpak", "org.example.App"),
+            app_name: "Old Name".to_string(),
+            exe: "/usr/bin/flatpak".to_string(),
+            flatpak_app_id: "org.example.App".to_string(),
+            last_play_time: 1700000000,
+            ..Default::default()
+        };
+        shortcuts.insert_shortcut(legacy.clone());
+        shortcuts.shortcuts.swap(2, 3);
+
+        let flatpak = ShortcutEntry {
+            appid: generate_steam_app_id("/usr/bin/flatpak", "Example"),
+            app_name: "Example".to_string(),
+            exe: "\"/usr/bin/flatpak\"".to_string(),
+            flatpak_app_id: "org.example.App".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(legacy.appid, shortcuts.insert_shortcut(flatpak));
+        assert_eq!(4, shortcuts.shortcuts.len());
+        assert_eq!("Example", shortcuts.shortcuts[2].app_name);
+        assert_eq!(1700000000, shortcuts.shortcuts[2].last_play_time);
+
+        let renamed = ShortcutEntry {
+            appid: 1234,
+            app_name: "Game 1".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(1, shortcuts.insert_shortcut(renamed));
+        let new = ShortcutEntry {
+            appid: 1234,
+            app_name: "New".to_string(),
+            exe: "\"/usr/bin/new\"".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(1234, shortcuts.insert_shortcut(new));
+        assert_eq!(5, shortcuts.shortcuts.len());
+    }
+
+    #[test]
+    fn test_insert_keeps_actions_apart_from_the_legacy_shortcut() {
+        let mut shortcuts = example_shortcuts();
+        let legacy = ShortcutEntry {
+            appid: generate_app_id("/usr/bin/flatpak", "org.example.App"),
+            app_name: "Example".to_string(),
+            exe: "/usr/bin/flatpak".to_string(),
+            flatpak_app_id: "org.example.App".to_string(),
+            ..Default::default()
+        };
+        shortcuts.insert_shortcut(legacy.clone());
+
+        let main = ShortcutEntry {
+            appid: generate_steam_app_id("/usr/bin/flatpak", "Example"),
+            app_name: "Example".to_string(),
+            exe: "\"/usr/bin/flatpak\"".to_string(),
+            flatpak_app_id: "org.example.App".to_string(),
+            ..Default::default()
+        };
+        let action = ShortcutEntry {
+            appid: generate_steam_app_id("/usr/bin/flatpak", "Example — Private"),
+            app_name: "Example — Private".to_string(),
+            flatpak_action_id: "private".to_string(),
+            ..main.clone()
+        };
+        let mut new_shortcuts = vec![main, action];
+        for shortcut in &mut new_shortcuts {
+            if let Some(index) = shortcuts.find_existing(shortcut) {
+                shortcut.appid = shortcuts.shortcuts[index].appid;
+            }
+        }
+        let appids: Vec<u32> = new_shortcuts
+            .into_iter()
+            .map(|shortcut| shortcuts.insert_shortcut(shortcut))
+            .collect();
+
+        assert_eq!(legacy.appid, appids[0]);
+        assert_ne!(legacy.appid, appids[1]);
+        assert_eq!(5, shortcuts.shortcuts.len());
+        assert_eq!("Example", shortcuts.shortcuts[3].app_name);
+        assert_eq!("Example — Private", shortcuts.shortcuts[4].app_name);
+    }
+
+    fn example_shortcuts() -> Shortcuts {
+        Shortcuts {
+            shortcuts: (0..3)
+                .map(|i| ShortcutEntry {
+                    appid: i,
+                    app_name: format!("Game {i}"),
+                    tags: vec!["Favorites".to_string()],
+                    extra_fields: vec![Entry::new("NewFloatKey", Node::Float(0.5))],
+                    ..Default::default()
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_truncated_input_is_an_error() {
+        let bytes = example_shortcuts().to_bytes();
+
+        for length in 0..bytes.len() {
+            assert!(Shortcuts::try_from_bytes(&bytes[..length]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_corrupt_input_does_not_panic() {
+        let bytes = example_shortcuts().to_bytes();
+        let mut seed: u64 = 0x2545F4914F6CDD1D;
+        let mut next_random = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed as usize
+        };
+
+        for _ in 0..10_000 {
+            let mut corrupt = bytes.clone();
+            for _ in 0..(next_random() % 4 + 1) {
+                let at = next_random() % corrupt.len();
+                match next_random() % 3 {
+                    0 => corrupt[at] = next_random() as u8,
+                    1 => corrupt.insert(at, next_random() as u8),
+                    _ => {
+                        corrupt.remove(at);
+                    }
+                }
+            }
+            let _ = Shortcuts::try_from_bytes(&corrupt);
+        }
+
+        let deeply_nested: Vec<u8> = [0x00, b'a', 0x00].repeat(100_000);
+        assert!(Shortcuts::try_from_bytes(&deeply_nested).is_err());
+    }
+
+    #[test]
+    fn test_mistyped_known_keys_are_kept() {
+        let mut entries = ShortcutEntry::default().to_entries();
+        let last_play_time = entries
+            .iter_mut()
+            .find(|e| e.key == "LastPlayTime")
+            .unwrap();
+        last_play_time.value = Node::String("yesterday".to_string());
+        let bytes = vdf::binary::to_bytes(&[Entry::new(
+            "shortcuts",
+            Node::Map(vec![Entry::new("0", Node::Map(entries))]),
+        )]);
+
+        let parsed = Shortcuts::try_from_bytes(&bytes).unwrap();
+
+        assert_eq!(0, parsed.shortcuts[0].last_play_time);
+        assert_eq!(
+            vec![Entry::new(
+                "LastPlayTime",
+                Node::String("yesterday".to_string())
+            )],
+            parsed.shortcuts[0].extra_fields
+        );
+        assert_eq!(bytes, parsed.to_bytes());
+    }
+
+    #[test]
+    fn test_errors_report_offset_and_index() {
+        let shortcuts = example_shortcuts();
+        let mut entries = shortcuts
+            .shortcuts
+            .iter()
+            .map(|s| Entry::new("0", Node::Map(s.to_entries())))
+            .collect::<Vec<Entry>>();
+        entries[1].key = "5".to_string();
+        let bytes = vdf::binary::to_bytes(&[Entry::new("shortcuts", Node::Map(entries))]);
+
+        match Shortcuts::try_from_bytes(&bytes) {
+            Err(ParsingError::ShortcutsEntriesDoNotAlign { offset, index, key }) => {
+                assert_eq!(1, index);
+                assert_eq!("5", key);
+                assert_eq!(&bytes[offset..offset + 3], b"\x005\x00");
+            }
+            result => panic!("Unexpected result {result:?}"),
+        }
+
+        let truncated = &bytes[..bytes.len() - 2];
+        let err = Shortcuts::try_from_bytes(truncated).unwrap_err();
+        assert_eq!(
+            format!(
+                "map is not closed at byte {} in \"shortcuts\"",
+                truncated.len()
+            ),
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn test_save_keeps_rotating_backups() {
+        let dir = TempDir::new("save");
+        let path = dir.join("shortcuts.vdf");
+
+        let shortcuts = example_shortcuts();
+        for _ in 0..MAX_BACKUPS + 3 {
+            shortcuts.save_to(&path).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+
+        let saved = Shortcuts::from_path(&path).unwrap();
+        assert_eq!(shortcuts.to_bytes(), saved.to_bytes());
+        let backups = list_backups(&path).unwrap();
+        assert_eq!(MAX_BACKUPS, backups.len());
+        assert_eq!(MAX_BACKUPS + 1, std::fs::read_dir(&dir).unwrap().count());
+    }
+
+    #[test]
+    fn test_failed_save_removes_the_temporary_file() {
+        let dir = TempDir::new("failed_save");
+        let path = dir.join("shortcuts.vdf");
+        std::fs::create_dir_all(path.join("not_empty")).unwrap();
+
+        assert!(example_shortcuts().save_to(&path).is_err());
+        assert_eq!(1, std::fs::read_dir(&dir).unwrap().count());
+    }
+}
@@ -1,17 +1,46 @@
+use crate::vdf::binary::binary_vdf_error::BinaryVdfError;
 use std::error::Error;
-use strum::Display;
+use std::fmt::{Display, Formatter};
 
-#[derive(Debug, Display)]
+#[derive(Debug)]
 pub enum ParsingError {
-    WrongStartingByte,
-    ShortcutsNotFound,
-    ShortcutsEntriesDoNotAlign,
-    InvalidMapType(u8),
-    FailedToParseString,
-    FailedToParseInteger,
-    UnknownStringKey(String),
-    UnknownIntegerKey(String),
-    CurrentShortcutEntryIsEmpty,
+    Vdf(BinaryVdfError),
+    ShortcutsNotFound {
+        offset: usize,
+    },
+    ShortcutsEntriesDoNotAlign {
+        offset: usize,
+        index: usize,
+        key: String,
+    },
+    ShortcutIsNotAMap {
+        offset: usize,
+        index: usize,
+    },
+}
+
+impl Display for ParsingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParsingError::Vdf(err) => write!(f, "{err}"),
+            ParsingError::ShortcutsNotFound { offset } => {
+                write!(f, "no \"shortcuts\" map found at byte {offset}")
+            }
+            ParsingError::ShortcutsEntriesDoNotAlign { offset, index, key } => write!(
+                f,
+                "shortcut {index} at byte {offset} has the unexpected key \"{key}\""
+            ),
+            ParsingError::ShortcutIsNotAMap { offset, index } => {
+                write!(f, "shortcut {index} at byte {offset} is not a map")
+            }
+        }
+    }
 }
 
 impl Error for ParsingError {}
+
+impl From<BinaryVdfError> for ParsingError {
+    fn from(value: BinaryVdfError) -> Self {
+        ParsingError::Vdf(value)
+    }
+}
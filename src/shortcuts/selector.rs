@@ -0,0 +1,142 @@
+use crate::config::SelectShortcuts;
+use crate::shortcuts::shortcut::ShortcutEntry;
+use regex::Regex;
+
+/// Matches shortcuts against every criterion that is set.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ShortcutSelector {
+    pub appid: Option<u32>,
+    pub name: Option<String>,
+    pub glob: Option<glob::Pattern>,
+    pub regex: Option<Regex>,
+    pub flatpak_id: Option<String>,
+}
+
+impl ShortcutSelector {
+    pub(crate) fn matches(&self, shortcut: &ShortcutEntry) -> bool {
+        let exe = shortcut.exe.trim_matches('"');
+
+        self.appid.is_none_or(|appid| shortcut.appid == appid)
+            && self
+                .name
+                .as_ref()
+                .is_none_or(|name| &shortcut.app_name == name)
+            && self
+                .glob
+                .as_ref()
+                .is_none_or(|glob| glob.matches(&shortcut.app_name) || glob.matches(exe))
+            && self
+                .regex
+                .as_ref()
+                .is_none_or(|regex| regex.is_match(&shortcut.app_name) || regex.is_match(exe))
+            && self
+                .flatpak_id
+                .as_ref()
+                .is_none_or(|flatpak_id| is_flatpak(shortcut, flatpak_id))
+    }
+}
+
+impl TryFrom<&SelectShortcuts> for ShortcutSelector {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &SelectShortcuts) -> Result<Self, Self::Error> {
+        Ok(Self {
+            appid: value.appid,
+            name: value.name.clone(),
+            glob: value.glob.as_deref().map(glob::Pattern::new).transpose()?,
+            regex: value.regex.as_deref().map(Regex::new).transpose()?,
+            flatpak_id: value.flatpak_id.clone(),
+        })
+    }
+}
+
+/// Shortcuts of Flatpaks either store the id or pass it to `flatpak run`.
+fn is_flatpak(shortcut: &ShortcutEntry, flatpak_id: &str) -> bool {
+    shortcut.flatpak_app_id == flatpak_id
+        || shortcut
+            .launch_options
+            .split_whitespace()
+            .any(|argument| argument.trim_matches('"') == flatpak_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shortcuts::selector::ShortcutSelector;
+    use crate::shortcuts::shortcut::ShortcutEntry;
+
+    #[test]
+    fn test_selector() {
+        let firefox = ShortcutEntry {
+            appid: 1,
+            app_name: "Firefox".to_string(),
+            exe: "\"/usr/bin/flatpak\"".to_string(),
+            launch_options: "\"run\" \"org.mozilla.firefox\"".to_string(),
+            ..Default::default()
+        };
+        let game = ShortcutEntry {
+            appid: 2,
+            app_name: "Some Game".to_string(),
+            exe: "/home/user/Games/game.sh".to_string(),
+            ..Default::default()
+        };
+        let select = |selector: ShortcutSelector| {
+            [&firefox, &game]
+                .into_iter()
+                .filter(|s| selector.matches(s))
+                .map(|s| s.appid)
+                .collect::<Vec<u32>>()
+        };
+
+        assert_eq!(vec![1, 2], select(ShortcutSelector::default()));
+        assert_eq!(
+            vec![2],
+            select(ShortcutSelector {
+                appid: Some(2),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            vec![1],
+            select(ShortcutSelector {
+                name: Some("Firefox".to_string()),
+                ..Default::default()
+            })
+        );
+        assert!(
+            select(ShortcutSelector {
+                name: Some("firefox".to_string()),
+                ..Default::default()
+            })
+            .is_empty()
+        );
+        assert_eq!(
+            vec![2],
+            select(ShortcutSelector {
+                glob: Some(glob::Pattern::new("/home/*/Games/*").unwrap()),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            vec![1],
+            select(ShortcutSelector {
+                regex: Some(regex::Regex::new("(?i)^fire").unwrap()),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            vec![1],
+            select(ShortcutSelector {
+                flatpak_id: Some("org.mozilla.firefox".to_string()),
+                ..Default::default()
+            })
+        );
+        assert!(
+            select(ShortcutSelector {
+                appid: Some(2),
+                name: Some("Firefox".to_string()),
+                ..Default::default()
+            })
+            .is_empty()
+        );
+    }
+}
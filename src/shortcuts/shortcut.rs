@@ -1,5 +1,9 @@
+use crate::flatpak::FLATPAK_EXE;
+use crate::utils::{generate_app_id, generate_game_id};
+use crate::vdf::binary::node::{Entry, Node};
+use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
-use std::fmt::Debug;
+use std::fmt::{Debug, Display};
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ShortcutEntry {
@@ -20,6 +24,250 @@ pub struct ShortcutEntry {
     pub last_play_time: u32,
     pub flatpak_app_id: String,
     pub sort_as: String,
-    #[allow(dead_code)]
     pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_fields: Vec<Entry>,
+    /// The keys in the order they were read, and whether each went to `extra_fields`, so
+    /// unknown keys are written back where they were.
+    #[serde(skip)]
+    pub key_order: Vec<(String, bool)>,
+    /// The desktop action a Flatpak shortcut launches. Earlier versions hashed it into the
+    /// appid, it is not stored in the shortcuts file.
+    #[serde(skip)]
+    pub flatpak_action_id: String,
 }
+
+impl ShortcutEntry {
+    pub(crate) fn game_id(&self) -> u64 {
+        generate_game_id(self.appid)
+    }
+
+    /// The unquoted exe and what earlier versions hashed instead of the app name: the
+    /// Flatpak id, followed by the action id for desktop actions, for Flatpak shortcuts,
+    /// else the launch options.
+    pub(crate) fn legacy_app_id_inputs(&self) -> (&str, String) {
+        match (
+            self.flatpak_app_id.is_empty(),
+            self.flatpak_action_id.is_empty(),
+        ) {
+            (true, _) => (self.exe.trim_matches('"'), self.launch_options.clone()),
+            (false, true) => (FLATPAK_EXE, self.flatpak_app_id.clone()),
+            (false, false) => (
+                FLATPAK_EXE,
+                format!("{} {}", self.flatpak_app_id, self.flatpak_action_id),
+            ),
+        }
+    }
+
+    /// Whether `other` is this shortcut created again, by an earlier version or with the
+    /// other appid algorithm: this appid is the legacy appid of `other`, or both have the
+    /// same exe and name.
+    pub(crate) fn is_same_shortcut(&self, other: &ShortcutEntry) -> bool {
+        let (exe, legacy_name) = other.legacy_app_id_inputs();
+        self.appid == generate_app_id(exe, &legacy_name)
+            || (self.exe.trim_matches('"') == exe && self.app_name == other.app_name)
+    }
+
+    /// Entries that are unknown, or known but of another type than expected, like a
+    /// `LastPlayTime` another tool wrote as a string, are kept in `extra_fields`.
+    pub(crate) fn from_entries(entries: Vec<Entry>) -> Self {
+        let mut rtn = Self::default();
+
+        for entry in entries {
+            let key = entry.key.clone();
+            let extra_count = rtn.extra_fields.len();
+            match (entry.key.as_str(), entry.value) {
+                ("appid", Node::Int32(value)) => rtn.appid = value as u32,
+                ("AppName", Node::String(value)) => rtn.app_name = value,
+                ("Exe", Node::String(value)) => rtn.exe = value,
+                ("StartDir", Node::String(value)) => rtn.start_dir = value,
+                ("icon", Node::String(value)) => rtn.icon = value,
+                ("ShortcutPath", Node::String(value)) => rtn.shortcut_path = value,
+                ("LaunchOptions", Node::String(value)) => rtn.launch_options = value,
+                ("IsHidden", Node::Int32(value)) => rtn.is_hidden = value as u32,
+                ("AllowDesktopConfig", Node::Int32(value)) => {
+                    rtn.allow_desktop_config = value as u32
+                }
+                ("AllowOverlay", Node::Int32(value)) => rtn.allow_overlay = value as u32,
+                ("OpenVR", Node::Int32(value)) => rtn.open_vr = value as u32,
+                ("Devkit", Node::Int32(value)) => rtn.devkit = value as u32,
+                ("DevkitGameID", Node::String(value)) => rtn.devkit_game_id = value,
+                ("DevkitOverrideAppID", Node::Int32(value)) => {
+                    rtn.devkit_override_app_id = value as u32
+                }
+                ("LastPlayTime", Node::Int32(value)) => rtn.last_play_time = value as u32,
+                ("FlatpakAppID", Node::String(value)) => rtn.flatpak_app_id = value,
+                ("sortas", Node::String(value)) => rtn.sort_as = value,
+                ("tags", Node::Map(tags)) => {
+                    rtn.tags = tags
+                        .into_iter()
+                        .filter_map(|tag| match tag.value {
+                            Node::String(value) => Some(value),
+                            _ => None,
+                        })
+                        .collect()
+                }
+                (_, value) => rtn.extra_fields.push(Entry {
+                    key: entry.key,
+                    value,
+                }),
+            }
+            let is_extra = rtn.extra_fields.len() > extra_count;
+            rtn.key_order.push((key, is_extra));
+        }
+
+        rtn
+    }
+
+    pub(crate) fn to_entries(&self) -> Vec<Entry> {
+        let int32 = |value: u32| Node::Int32(value as i32);
+        let string = |value: &str| Node::String(value.to_string());
+        let tags = self
+            .tags
+            .iter()
+            .enumerate()
+            .map(|(i, tag)| Entry::new(&i.to_string(), string(tag)))
+            .collect();
+
+        let mut known = vec![
+            Entry::new("appid", int32(self.appid)),
+            Entry::new("AppName", string(&self.app_name)),
+            Entry::new("Exe", string(&self.exe)),
+            Entry::new("StartDir", string(&self.start_dir)),
+            Entry::new("icon", string(&self.icon)),
+            Entry::new("ShortcutPath", string(&self.shortcut_path)),
+            Entry::new("LaunchOptions", string(&self.launch_options)),
+            Entry::new("IsHidden", int32(self.is_hidden)),
+            Entry::new("AllowDesktopConfig", int32(self.allow_desktop_config)),
+            Entry::new("AllowOverlay", int32(self.allow_overlay)),
+            Entry::new("OpenVR", int32(self.open_vr)),
+            Entry::new("Devkit", int32(self.devkit)),
+            Entry::new("DevkitGameID", string(&self.devkit_game_id)),
+            Entry::new("DevkitOverrideAppID", int32(self.devkit_override_app_id)),
+            Entry::new("LastPlayTime", int32(self.last_play_time)),
+            Entry::new("FlatpakAppID", string(&self.flatpak_app_id)),
+            Entry::new("sortas", string(&self.sort_as)),
+            Entry::new("tags", Node::Map(tags)),
+        ];
+        let mut extra = self.extra_fields.clone();
+
+        let mut rtn = Vec::with_capacity(known.len() + extra.len());
+        for (key, is_extra) in &self.key_order {
+            let remaining = if *is_extra { &mut extra } else { &mut known };
+            if let Some(at) = remaining.iter().position(|e| &e.key == key) {
+                rtn.push(remaining.remove(at));
+            }
+        }
+        // A known key that was only read with another type stays in the extra fields alone.
+        known.retain(|k| !self.extra_fields.iter().any(|e| e.key == k.key));
+        rtn.extend(known);
+        rtn.extend(extra);
+        rtn
+    }
+
+    /// Sets the entry with the VDF key or field name, e.g. `LaunchOptions` or `launch_options`,
+    /// parsing the value as the type the entry already has. Unknown keys are added as strings
+    /// and `tags` takes a comma separated list.
+    pub(crate) fn set_field(&mut self, key: &str, value: &str) -> anyhow::Result<()> {
+        let mut entries = self.to_entries();
+        match entries.iter_mut().find(|e| is_same_key(&e.key, key)) {
+            Some(entry) => entry.value = parse_like(&entry.value, value)?,
+            None => entries.push(Entry::new(key, Node::String(value.to_string()))),
+        }
+        *self = Self::from_entries(entries);
+        Ok(())
+    }
+
+    /// Removes an unknown key. Known keys always exist and can only be set.
+    pub(crate) fn unset_field(&mut self, key: &str) -> anyhow::Result<()> {
+        if KNOWN_KEYS.iter().any(|k| is_same_key(k, key)) {
+            return Err(anyhow!("{key} is a known key and can not be removed"));
+        }
+        let count = self.extra_fields.len();
+        self.extra_fields.retain(|e| !is_same_key(&e.key, key));
+        if count == self.extra_fields.len() {
+            return Err(anyhow!("The shortcut has no key {key}"));
+        }
+        Ok(())
+    }
+}
+
+/// Keys match case-insensitively and without underscores, so field names match VDF keys.
+fn is_same_key(vdf_key: &str, key: &str) -> bool {
+    let normalize = |k: &str| k.replace('_', "").to_lowercase();
+    normalize(vdf_key) == normalize(key)
+}
+
+fn parse_like(node: &Node, value: &str) -> anyhow::Result<Node> {
+    let to_error =
+        |err: &dyn Display| anyhow!("{value} is not a valid {} - {err}", node_name(node));
+    let rtn = match node {
+        Node::Map(_) => Node::Map(
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .enumerate()
+                .map(|(i, tag)| Entry::new(&i.to_string(), Node::String(tag.to_string())))
+                .collect(),
+        ),
+        Node::String(_) => Node::String(value.to_string()),
+        Node::Int32(_) => Node::Int32(match value {
+            "true" => 1,
+            "false" => 0,
+            _ => value
+                .parse::<i32>()
+                .or_else(|_| value.parse::<u32>().map(|v| v as i32))
+                .map_err(|err| to_error(&err))?,
+        }),
+        Node::Float(_) => Node::Float(value.parse().map_err(|err| to_error(&err))?),
+        Node::Ptr(_) => Node::Ptr(value.parse().map_err(|err| to_error(&err))?),
+        Node::WString(_) => Node::WString(value.encode_utf16().collect()),
+        Node::Color(_) => {
+            let bytes: Vec<u8> = value
+                .split(',')
+                .map(|b| b.trim().parse())
+                .collect::<Result<_, _>>()
+                .map_err(|err| to_error(&err))?;
+            Node::Color(
+                bytes
+                    .try_into()
+                    .map_err(|_| to_error(&"expected four bytes"))?,
+            )
+        }
+        Node::UInt64(_) => Node::UInt64(value.parse().map_err(|err| to_error(&err))?),
+        Node::Int64(_) => Node::Int64(value.parse().map_err(|err| to_error(&err))?),
+    };
+    Ok(rtn)
+}
+
+fn node_name(node: &Node) -> &'static str {
+    match node {
+        Node::Map(_) => "list",
+        Node::String(_) | Node::WString(_) => "string",
+        Node::Int32(_) | Node::Ptr(_) | Node::UInt64(_) | Node::Int64(_) => "number",
+        Node::Float(_) => "float",
+        Node::Color(_) => "color",
+    }
+}
+
+const KNOWN_KEYS: [&str; 18] = [
+    "appid",
+    "AppName",
+    "Exe",
+    "StartDir",
+    "icon",
+    "ShortcutPath",
+    "LaunchOptions",
+    "IsHidden",
+    "AllowDesktopConfig",
+    "AllowOverlay",
+    "OpenVR",
+    "Devkit",
+    "DevkitGameID",
+    "DevkitOverrideAppID",
+    "LastPlayTime",
+    "FlatpakAppID",
+    "sortas",
+    "tags",
+];
@@ -0,0 +1,329 @@
+use crate::shortcuts::Shortcuts;
+use anyhow::anyhow;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+/// Version of the archive layout, stored in the manifest of every snapshot.
+pub(crate) const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+const SHORTCUTS_FILE_NAME: &str = "shortcuts.vdf";
+const GRID_FOLDER_NAME: &str = "grid";
+const SNAPSHOT_EXTENSION: &str = "tar";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct SnapshotManifest {
+    pub version: u32,
+    pub created: DateTime<Local>,
+    pub shortcuts_file: PathBuf,
+    pub grid_folder: PathBuf,
+    pub shortcut_count: usize,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Snapshot {
+    pub name: String,
+    pub path: PathBuf,
+    pub manifest: SnapshotManifest,
+}
+
+pub(crate) fn get_snapshot_folder(data_dir: &Path) -> PathBuf {
+    data_dir.join("steam_shortcut_snapshots")
+}
+
+/// Archives the shortcuts file and the grid folder as `snapshot-{timestamp}.tar`.
+/// A missing shortcuts file is archived as one without shortcuts.
+pub(crate) fn create_snapshot(
+    shortcuts_file: &Path,
+    grid_folder: &Path,
+    snapshot_folder: &Path,
+) -> anyhow::Result<Snapshot> {
+    let shortcuts = match shortcuts_file.exists() {
+        true => Shortcuts::from_path(shortcuts_file)
+            .ok_or(anyhow!("Could not read {}", shortcuts_file.display()))?,
+        false => Shortcuts::default(),
+    };
+    let created = Local::now();
+    let manifest = SnapshotManifest {
+        version: SNAPSHOT_FORMAT_VERSION,
+        created,
+        shortcuts_file: shortcuts_file.to_path_buf(),
+        grid_folder: grid_folder.to_path_buf(),
+        shortcut_count: shortcuts.shortcuts.len(),
+    };
+
+    std::fs::create_dir_all(snapshot_folder)?;
+    let name = format!("snapshot-{}", created.format("%Y%m%d-%H%M%S%.3f"));
+    let path = snapshot_folder.join(format!("{name}.{SNAPSHOT_EXTENSION}"));
+
+    let mut builder = tar::Builder::new(File::create_new(&path)?);
+    let manifest_json = serde_json::to_vec_pretty(&manifest)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(manifest_json.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(created.timestamp().max(0) as u64);
+    builder.append_data(&mut header, MANIFEST_FILE_NAME, manifest_json.as_slice())?;
+    if shortcuts_file.exists() {
+        builder.append_path_with_name(shortcuts_file, SHORTCUTS_FILE_NAME)?;
+    } else {
+        let bytes = shortcuts.to_bytes();
+        let mut header = tar::Header::new_gnu();
+        header.set_size(bytes.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(created.timestamp().max(0) as u64);
+        builder.append_data(&mut header, SHORTCUTS_FILE_NAME, bytes.as_slice())?;
+    }
+    if grid_folder.is_dir() {
+        builder.append_dir_all(GRID_FOLDER_NAME, grid_folder)?;
+    }
+    builder.into_inner()?.sync_all()?;
+
+    Ok(Snapshot {
+        name,
+        path,
+        manifest,
+    })
+}
+
+/// All snapshots in the folder, oldest first. The folder is shared by all Steam users, with
+/// `shortcuts_file` only the snapshots taken of it are listed.
+pub(crate) fn list_snapshots(
+    snapshot_folder: &Path,
+    shortcuts_file: Option<&Path>,
+) -> anyhow::Result<Vec<Snapshot>> {
+    if !snapshot_folder.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut rtn = Vec::new();
+    for dir_entry in std::fs::read_dir(snapshot_folder)? {
+        let path = dir_entry?.path();
+        if path.extension().is_none_or(|e| e != SNAPSHOT_EXTENSION) {
+            continue;
+        }
+        match read_manifest(&path) {
+            Ok(manifest) if shortcuts_file.is_some_and(|f| f != manifest.shortcuts_file) => {}
+            Ok(manifest) => rtn.push(Snapshot {
+                name: path
+                    .file_stem()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string(),
+                path,
+                manifest,
+            }),
+            Err(err) => println!("Skipping {} - {err}", path.display()),
+        }
+    }
+    rtn.sort_by_key(|s| s.manifest.created);
+
+    Ok(rtn)
+}
+
+/// Finds a snapshot by its name, file name or `latest`, among the snapshots of
+/// `shortcuts_file` if given.
+pub(crate) fn find_snapshot(
+    snapshot_folder: &Path,
+    name: &str,
+    shortcuts_file: Option<&Path>,
+) -> anyhow::Result<Snapshot> {
+    let mut snapshots = list_snapshots(snapshot_folder, shortcuts_file)?;
+    if name == "latest" {
+        return snapshots.pop().ok_or(anyhow!("There are no snapshots"));
+    }
+    snapshots
+        .into_iter()
+        .find(|s| s.name == name || s.path.file_name().is_some_and(|f| f == name))
+        .ok_or(anyhow!("Snapshot {name} not found"))
+}
+
+/// Replaces the shortcuts file and the grid folder with the content of the snapshot.
+/// The current grid folder is put back if anything fails. Snapshots of the shortcuts file
+/// of another user are refused.
+pub(crate) fn restore_snapshot(
+    snapshot: &Snapshot,
+    shortcuts_file: &Path,
+    grid_folder: &Path,
+) -> anyhow::Result<()> {
+    if snapshot.manifest.version > SNAPSHOT_FORMAT_VERSION {
+        return Err(anyhow!(
+            "Snapshot format version {} is newer than the supported version {SNAPSHOT_FORMAT_VERSION}",
+            snapshot.manifest.version
+        ));
+    }
+    if snapshot.manifest.shortcuts_file != shortcuts_file {
+        return Err(anyhow!(
+            "Snapshot was taken of {}, not of {}",
+            snapshot.manifest.shortcuts_file.display(),
+            shortcuts_file.display()
+        ));
+    }
+
+    let config_folder = grid_folder
+        .parent()
+        .ok_or(anyhow!("Grid folder has no parent"))?;
+    let staging_folder = config_folder.join(format!(".snapshot-restore-{}", std::process::id()));
+    std::fs::create_dir_all(&staging_folder)?;
+
+    let result = restore_from_staging(snapshot, &staging_folder, shortcuts_file, grid_folder);
+    std::fs::remove_dir_all(&staging_folder).ok();
+    result
+}
+
+fn restore_from_staging(
+    snapshot: &Snapshot,
+    staging_folder: &Path,
+    shortcuts_file: &Path,
+    grid_folder: &Path,
+) -> anyhow::Result<()> {
+    tar::Archive::new(File::open(&snapshot.path)?).unpack(staging_folder)?;
+
+    let bytes = std::fs::read(staging_folder.join(SHORTCUTS_FILE_NAME))?;
+    let shortcuts = Shortcuts::try_from_bytes(&bytes)
+        .map_err(|err| anyhow!("Shortcuts in the snapshot can not be parsed - {err}"))?;
+
+    let previous_grid_folder = staging_folder.join("previous_grid");
+    if grid_folder.exists() {
+        std::fs::rename(grid_folder, &previous_grid_folder)?;
+    }
+
+    let result = replace_grid_folder(&staging_folder.join(GRID_FOLDER_NAME), grid_folder)
+        .and_then(|_| shortcuts.save_to(shortcuts_file));
+    if let Err(err) = &result
+        && previous_grid_folder.exists()
+    {
+        std::fs::remove_dir_all(grid_folder).ok();
+        if let Err(rename_err) = std::fs::rename(&previous_grid_folder, grid_folder) {
+            return Err(anyhow!(
+                "{err} - the previous grid folder could not be put back - {rename_err}"
+            ));
+        }
+    }
+    result
+}
+
+fn replace_grid_folder(staged_grid_folder: &Path, grid_folder: &Path) -> anyhow::Result<()> {
+    if staged_grid_folder.is_dir() {
+        std::fs::rename(staged_grid_folder, grid_folder)?;
+    } else {
+        std::fs::create_dir_all(grid_folder)?;
+    }
+    Ok(())
+}
+
+fn read_manifest(path: &Path) -> anyhow::Result<SnapshotManifest> {
+    let mut archive = tar::Archive::new(File::open(path)?);
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if entry.path()? == Path::new(MANIFEST_FILE_NAME) {
+            return Ok(serde_json::from_reader(entry)?);
+        }
+    }
+    Err(anyhow!("Snapshot has no {MANIFEST_FILE_NAME}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shortcuts::Shortcuts;
+    use crate::shortcuts::shortcut::ShortcutEntry;
+    use crate::snapshots::{create_snapshot, find_snapshot, list_snapshots, restore_snapshot};
+    use crate::utils::TempDir;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_snapshot_and_restore() {
+        let dir = TempDir::new("snapshot");
+        let config_folder = dir.join("config");
+        let grid_folder = config_folder.join("grid");
+        let snapshot_folder = dir.join("snapshots");
+        std::fs::create_dir_all(&grid_folder).unwrap();
+        let shortcuts_file = config_folder.join("shortcuts.vdf");
+
+        let mut shortcuts = Shortcuts::default();
+        shortcuts.insert_shortcut(ShortcutEntry {
+            appid: 1,
+            app_name: "Before".to_string(),
+            ..Default::default()
+        });
+        shortcuts.save_to(&shortcuts_file).unwrap();
+        std::fs::write(grid_folder.join("1p.jpg"), b"before").unwrap();
+
+        let snapshot = create_snapshot(&shortcuts_file, &grid_folder, &snapshot_folder).unwrap();
+        assert_eq!(1, snapshot.manifest.shortcut_count);
+
+        shortcuts.shortcuts[0].app_name = "After".to_string();
+        shortcuts.save_to(&shortcuts_file).unwrap();
+        std::fs::remove_file(grid_folder.join("1p.jpg")).unwrap();
+        std::fs::write(grid_folder.join("2p.jpg"), b"after").unwrap();
+
+        assert_eq!(1, list_snapshots(&snapshot_folder, None).unwrap().len());
+        let latest = find_snapshot(&snapshot_folder, "latest", Some(&shortcuts_file)).unwrap();
+        assert_eq!(snapshot.name, latest.name);
+        restore_snapshot(&latest, &shortcuts_file, &grid_folder).unwrap();
+
+        let restored = Shortcuts::from_path(&shortcuts_file).unwrap();
+        assert_eq!("Before", restored.shortcuts[0].app_name);
+        assert_eq!(
+            b"before".to_vec(),
+            std::fs::read(grid_folder.join("1p.jpg")).unwrap()
+        );
+        assert!(!grid_folder.join("2p.jpg").exists());
+    }
+
+    #[test]
+    fn test_failed_restore_keeps_the_grid_folder() {
+        let dir = TempDir::new("failed_restore");
+        let config_folder = dir.join("config");
+        let grid_folder = config_folder.join("grid");
+        let snapshot_folder = dir.join("snapshots");
+        std::fs::create_dir_all(&grid_folder).unwrap();
+        let shortcuts_file = config_folder.join("shortcuts.vdf");
+
+        let snapshot = create_snapshot(&shortcuts_file, &grid_folder, &snapshot_folder).unwrap();
+        assert_eq!(0, snapshot.manifest.shortcut_count);
+
+        std::fs::write(grid_folder.join("1p.jpg"), b"current").unwrap();
+        std::fs::create_dir_all(shortcuts_file.join("not_empty")).unwrap();
+
+        assert!(restore_snapshot(&snapshot, &shortcuts_file, &grid_folder).is_err());
+        assert_eq!(
+            b"current".to_vec(),
+            std::fs::read(grid_folder.join("1p.jpg")).unwrap()
+        );
+        assert_eq!(2, std::fs::read_dir(&config_folder).unwrap().count());
+    }
+
+    #[test]
+    fn test_snapshots_of_other_users_are_not_restored() {
+        let dir = TempDir::new("snapshot_users");
+        let snapshot_folder = dir.join("snapshots");
+        let users: Vec<(PathBuf, PathBuf)> = ["1", "2"]
+            .iter()
+            .map(|user| {
+                let config_folder = dir.join(user).join("config");
+                std::fs::create_dir_all(config_folder.join("grid")).unwrap();
+                (
+                    config_folder.join("shortcuts.vdf"),
+                    config_folder.join("grid"),
+                )
+            })
+            .collect();
+        let (shortcuts_file, grid_folder) = &users[0];
+        let (other_shortcuts_file, other_grid_folder) = &users[1];
+
+        let snapshot = create_snapshot(shortcuts_file, grid_folder, &snapshot_folder).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        create_snapshot(other_shortcuts_file, other_grid_folder, &snapshot_folder).unwrap();
+
+        assert_eq!(2, list_snapshots(&snapshot_folder, None).unwrap().len());
+        let own = list_snapshots(&snapshot_folder, Some(shortcuts_file)).unwrap();
+        assert_eq!(1, own.len());
+        let latest = find_snapshot(&snapshot_folder, "latest", Some(shortcuts_file)).unwrap();
+        assert_eq!(snapshot.name, latest.name);
+
+        assert!(restore_snapshot(&snapshot, other_shortcuts_file, other_grid_folder).is_err());
+        assert!(!other_shortcuts_file.exists());
+    }
+}
@@ -1,126 +1,226 @@
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::ops::SubAssign;
-use std::path::PathBuf;
+use crate::steam::steam_id::SteamId;
+use crate::vdf;
+use crate::vdf::text::node::Value;
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
 
-#[derive(Debug, Clone)]
-pub struct LoginUsers {
-    #[allow(dead_code)]
-    pub users: HashMap<String, HashMap<String, String>>,
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LoginUser {
+    pub steam_id: SteamId,
+    pub account_name: String,
+    pub persona_name: String,
+    pub most_recent: bool,
+    pub timestamp: u64,
 }
 
-impl Default for LoginUsers {
-    fn default() -> Self {
-        Self {
-            users: HashMap::with_capacity(1),
-        }
+impl LoginUser {
+    pub(crate) fn account_id(&self) -> u32 {
+        self.steam_id.account_id()
     }
 }
 
-// enum ParserState {
-//     Start,
-//     Users,
-//     User,
-// }
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LoginUsers {
+    pub users: Vec<LoginUser>,
+}
 
 impl LoginUsers {
-    pub(crate) fn get_first_user_id(vdf_file: &PathBuf) -> Option<String> {
-        let file = File::open(vdf_file).ok()?;
-        let reader = BufReader::new(file);
-        let mut deep = 0;
-        let mut rtn = None;
-        for line_result in reader.lines() {
-            let line = line_result.ok()?;
-            let trim = line.trim();
-
-            let mut split = trim.split_ascii_whitespace();
-            if deep == 0 {
-                if split.next()? == "{" {
-                    deep = 1;
-                }
-            } else if deep == 1 {
-                let item = split.next()?;
-                if item.len() == 19 {
-                    let mut number: u64 = item[1..18].parse().ok()?;
-                    number.sub_assign(0x110000100000000);
-                    rtn = Some(number.to_string());
-                    break;
-                }
-            }
+    pub fn from_vdf_file(vdf_file: &Path) -> anyhow::Result<LoginUsers> {
+        let entries = vdf::text::from_path(vdf_file)?;
+        let users = entries
+            .iter()
+            .find(|e| e.key.eq_ignore_ascii_case("users"))
+            .and_then(|e| e.value.as_map())
+            .ok_or(anyhow!("No users found in {}", vdf_file.display()))?;
+
+        let mut rtn = Self::default();
+        for user in users {
+            let Ok(steam_id) = user.key.parse() else {
+                continue;
+            };
+            let get_string = |key: &str| {
+                user.value
+                    .get(key)
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            rtn.users.push(LoginUser {
+                steam_id,
+                account_name: get_string("AccountName"),
+                persona_name: get_string("PersonaName"),
+                most_recent: get_string("MostRecent") == "1",
+                timestamp: get_string("Timestamp").parse().unwrap_or_default(),
+            });
         }
-        rtn
+
+        Ok(rtn)
     }
 
-    // pub fn from_vdf_file(vdf_file: &Path) -> anyhow::Result<LoginUsers> {
-    //     let mut rtn = Self::default();
-    //     let mut state = ParserState::Start;
-    //     let file = File::open(vdf_file)?;
-    //     let reader = BufReader::new(file);
-    //     let mut deeps = 0;
-    //
-    //     for line_result in reader.lines() {
-    //         let line = line_result?;
-    //         let trim = line.trim();
-    //
-    //         let tokens = find_tokens(&line)?;
-    //
-    //         if let Some(&first) = tokens.first() {
-    //             match first {
-    //                 "{" => deeps += 1,
-    //                 "}" => deeps -= 1,
-    //                 _ => {}
-    //             }
-    //         }
-    //     }
-    //
-    //     if deeps != 0 {
-    //         return Err(anyhow!("VDF File is not correctly aligned"));
-    //     }
-    //
-    //     Ok(rtn)
-    // }
+    /// The user flagged as `MostRecent`, or the one with the latest `Timestamp`.
+    pub(crate) fn find_most_recent(&self) -> anyhow::Result<&LoginUser> {
+        let flagged: Vec<&LoginUser> = self.users.iter().filter(|u| u.most_recent).collect();
+        let candidates = if flagged.is_empty() {
+            self.users.iter().collect()
+        } else {
+            flagged
+        };
+
+        let latest = candidates
+            .iter()
+            .map(|u| u.timestamp)
+            .max()
+            .ok_or(anyhow!("There are no users in loginusers.vdf"))?;
+        let latest_users: Vec<&LoginUser> = candidates
+            .into_iter()
+            .filter(|u| u.timestamp == latest)
+            .collect();
+
+        match latest_users.as_slice() {
+            [user] => Ok(user),
+            users => Err(anyhow!(
+                "Can not decide which user logged in most recently: {}. Use --steam-user-id",
+                describe_users(users)
+            )),
+        }
+    }
+
+    /// Finds a user by any form of [`SteamId`], account name or persona name.
+    pub(crate) fn find_user(&self, query: &str) -> anyhow::Result<&LoginUser> {
+        if let Ok(steam_id) = query.parse::<SteamId>()
+            && let Some(user) = self
+                .users
+                .iter()
+                .find(|u| u.account_id() == steam_id.account_id())
+        {
+            return Ok(user);
+        }
+
+        if let Some(user) = self
+            .users
+            .iter()
+            .find(|u| u.account_name.eq_ignore_ascii_case(query))
+        {
+            return Ok(user);
+        }
+
+        let by_persona_name: Vec<&LoginUser> = self
+            .users
+            .iter()
+            .filter(|u| u.persona_name == query)
+            .collect();
+        match by_persona_name.as_slice() {
+            [user] => Ok(user),
+            [] => Err(anyhow!("No Steam user matches {query}")),
+            users => Err(anyhow!(
+                "Several Steam users are called {query}: {}. Use the account name or id",
+                describe_users(users)
+            )),
+        }
+    }
+}
+
+fn describe_users(users: &[&LoginUser]) -> String {
+    users
+        .iter()
+        .map(|u| format!("{} ({})", u.account_name, u.account_id()))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::steam::login_users::{LoginUser, LoginUsers};
+    use crate::steam::steam_id::SteamId;
+    use crate::utils::TempDir;
+
+    #[test]
+    fn test_login_users() {
+        let dir = TempDir::new("login_users");
+        let path = dir.join("loginusers.vdf");
+        std::fs::write(
+            &path,
+            r#""users"
+{
+	"76561198000000001"
+	{
+		"AccountName"		"first"
+		"PersonaName"		"First"
+		"RememberPassword"		"1"
+		"MostRecent"		"0"
+		"Timestamp"		"1700000000"
+	}
+	"76561198000000002"
+	{
+		"AccountName"		"second"
+		"PersonaName"		"Second"
+		"MostRecent"		"1"
+		"Timestamp"		"1710000000"
+	}
 }
+"#,
+        )
+        .unwrap();
 
-// fn find_tokens(line: &str) -> anyhow::Result<Vec<&str>> {
-//     let mut rtn = Vec::with_capacity(2);
-//     let bytes = line.as_bytes();
-//     let mut pos = 0;
-//     let mut in_token = false;
-//     let mut start = 0;
-//
-//     while pos < line.len() {
-//         let c = bytes[pos];
-//
-//         if in_token {
-//             if c == b'"' {
-//                 in_token = false;
-//                 rtn.push(&line[start..pos])
-//             }
-//         } else {
-//             match c {
-//                 b'"' => {
-//                     in_token = true;
-//                     start = pos;
-//                 }
-//                 b'{' => {
-//                     start = pos;
-//                     rtn.push(&line[start..pos + 1]);
-//                 }
-//                 b'}' => {
-//                     start = pos;
-//                     rtn.push(&line[start..pos + 1]);
-//                 }
-//                 _ => {}
-//             }
-//         }
-//
-//         pos += 1;
-//     }
-//
-//     if in_token {
-//         return Err(anyhow!("Token is not closed"));
-//     }
-//
-//     Ok(rtn)
-// }
+        let login_users = LoginUsers::from_vdf_file(&path).unwrap();
+
+        assert_eq!(2, login_users.users.len());
+        let second = &login_users.users[1];
+        assert_eq!("second", second.account_name);
+        assert_eq!("Second", second.persona_name);
+        assert!(second.most_recent);
+        assert_eq!(1710000000, second.timestamp);
+        assert_eq!(39734274, second.account_id());
+
+        assert_eq!(
+            "second",
+            login_users.find_most_recent().unwrap().account_name
+        );
+        assert_eq!(
+            "first",
+            login_users.find_user("39734273").unwrap().account_name
+        );
+        assert_eq!(
+            "first",
+            login_users
+                .find_user("76561198000000001")
+                .unwrap()
+                .account_name
+        );
+        assert_eq!(
+            "second",
+            login_users
+                .find_user("[U:1:39734274]")
+                .unwrap()
+                .account_name
+        );
+        assert_eq!(
+            "second",
+            login_users.find_user("SECOND").unwrap().account_name
+        );
+        assert_eq!(
+            "first",
+            login_users.find_user("First").unwrap().account_name
+        );
+        assert!(login_users.find_user("Third").is_err());
+    }
+
+    #[test]
+    fn test_ambiguous_users() {
+        let user = |account_name: &str, persona_name: &str, timestamp| LoginUser {
+            steam_id: SteamId::from_steam_id_64(76561198000000000 + timestamp),
+            account_name: account_name.to_string(),
+            persona_name: persona_name.to_string(),
+            most_recent: false,
+            timestamp,
+        };
+        let login_users = LoginUsers {
+            users: vec![user("a", "Same", 1), user("b", "Same", 1)],
+        };
+
+        assert!(login_users.find_most_recent().is_err());
+        assert!(login_users.find_user("Same").is_err());
+        assert_eq!("b", login_users.find_user("b").unwrap().account_name);
+    }
+}
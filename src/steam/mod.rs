@@ -1,17 +1,95 @@
 use crate::steam::app_list_result::AppListResult;
 use crate::steam::login_users::LoginUsers;
+use crate::steam::steam_id::SteamId;
 use anyhow::anyhow;
+use std::path::{Path, PathBuf};
 use textdistance::str::sift4_simple;
 
 mod app_list_result;
 pub mod login_users;
+pub(crate) mod running;
+pub mod steam_id;
+pub(crate) mod steam_user;
 
-pub(crate) fn get_user_id_fast() -> anyhow::Result<String> {
-    let login_users_path = dirs::home_dir()
-        .ok_or(anyhow!("Could not find home dir"))?
-        .join(".steam/steam/config/loginusers.vdf");
+/// Resolves the folder name below `userdata` of the user matching `query`
+/// or, without a query, of the user who logged in most recently.
+pub(crate) fn find_user_id(steam_root: &Path, query: Option<&str>) -> anyhow::Result<String> {
+    let userdata = steam_root.join("userdata");
+    let login_users = LoginUsers::from_vdf_file(&steam_root.join("config/loginusers.vdf"));
 
-    LoginUsers::get_first_user_id(&login_users_path).ok_or(anyhow!("Could not find first user_id"))
+    if let Some(query) = query {
+        if let Ok(steam_id) = query.parse::<SteamId>() {
+            let user_id = steam_id.account_id().to_string();
+            if userdata.join(&user_id).is_dir() {
+                return Ok(user_id);
+            }
+        }
+        return Ok(login_users?.find_user(query)?.account_id().to_string());
+    }
+
+    if let Ok(login_users) = login_users {
+        return Ok(login_users.find_most_recent()?.account_id().to_string());
+    }
+
+    let user_ids: Vec<String> = userdata
+        .read_dir()?
+        .filter_map(|e| {
+            let e = e.ok()?;
+            if e.file_name() == "0" || !e.path().is_dir() {
+                return None;
+            }
+            e.file_name().into_string().ok()
+        })
+        .collect();
+
+    match user_ids.as_slice() {
+        [user_id] => Ok(user_id.clone()),
+        [] => Err(anyhow!("There are no users in {}", userdata.display())),
+        _ => Err(anyhow!(
+            "Can not decide between the users {}. Use --steam-user-id",
+            user_ids.join(", ")
+        )),
+    }
+}
+
+/// Steam roots of native, Flatpak and Snap installations relative to the home directory.
+const STEAM_ROOT_CANDIDATES: [&str; 4] = [
+    ".steam/steam",
+    ".local/share/Steam",
+    ".var/app/com.valvesoftware.Steam/.local/share/Steam",
+    "snap/steam/common/.local/share/Steam",
+];
+
+/// All existing Steam roots below the home directory with symlinks resolved.
+/// Roots that contain a `userdata` folder come first.
+pub(crate) fn find_steam_roots(home_dir: &Path) -> Vec<PathBuf> {
+    let mut rtn: Vec<PathBuf> = Vec::new();
+    for candidate in STEAM_ROOT_CANDIDATES {
+        let Ok(root) = home_dir.join(candidate).canonicalize() else {
+            continue;
+        };
+        if root.is_dir() && !rtn.contains(&root) {
+            rtn.push(root);
+        }
+    }
+    rtn.sort_by_key(|root| !root.join("userdata").is_dir());
+    rtn
+}
+
+/// Whether the Steam root is one of the home folder, or below it.
+pub(crate) fn is_local_steam_root(home_dir: &Path, steam_root: &Path) -> bool {
+    let home_dir = home_dir.canonicalize().unwrap_or(home_dir.to_path_buf());
+    steam_root.starts_with(&home_dir)
+        || find_steam_roots(&home_dir).contains(&steam_root.to_path_buf())
+}
+
+pub(crate) fn get_steam_pid_files(home_dir: &Path, steam_root: &Path) -> Vec<PathBuf> {
+    vec![
+        home_dir.join(".steam/steam.pid"),
+        home_dir.join(".var/app/com.valvesoftware.Steam/.steam/steam.pid"),
+        home_dir.join("snap/steam/common/.steam/steam.pid"),
+        steam_root.join("steam.pid"),
+    ]
 }
 
 pub(crate) fn get_best_steam_app_id(game_name: &str) -> Option<u32> {
@@ -35,3 +113,67 @@ pub(crate) fn get_best_steam_app_id(game_name: &str) -> Option<u32> {
     );
     Some(result.app_id as u32)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::steam::{find_steam_roots, find_user_id, is_local_steam_root};
+    use crate::utils::TempDir;
+
+    #[test]
+    fn test_find_steam_roots() {
+        let home_dir = TempDir::new("steam_roots");
+        let native = home_dir.join(".local/share/Steam");
+        let flatpak = home_dir.join(".var/app/com.valvesoftware.Steam/.local/share/Steam");
+        std::fs::create_dir_all(&native).unwrap();
+        std::fs::create_dir_all(flatpak.join("userdata")).unwrap();
+        std::fs::create_dir_all(home_dir.join(".steam")).unwrap();
+        std::os::unix::fs::symlink(&native, home_dir.join(".steam/steam")).unwrap();
+
+        let roots = find_steam_roots(&home_dir);
+
+        assert_eq!(
+            vec![
+                flatpak.canonicalize().unwrap(),
+                native.canonicalize().unwrap()
+            ],
+            roots
+        );
+    }
+
+    #[test]
+    fn test_is_local_steam_root() {
+        let dir = TempDir::new("local_steam_root");
+        let home_dir = dir.join("home");
+        let outside = dir.join("games/Steam");
+        let copy = dir.join("copy/.local/share/Steam");
+        std::fs::create_dir_all(home_dir.join(".steam")).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::create_dir_all(&copy).unwrap();
+        std::os::unix::fs::symlink(&outside, home_dir.join(".steam/steam")).unwrap();
+
+        let outside = outside.canonicalize().unwrap();
+        assert!(is_local_steam_root(&home_dir, &outside));
+        assert!(is_local_steam_root(
+            &home_dir,
+            &home_dir.canonicalize().unwrap().join(".local/share/Steam")
+        ));
+        assert!(!is_local_steam_root(
+            &home_dir,
+            &copy.canonicalize().unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_find_user_id_without_login_users() {
+        let steam_root = TempDir::new("user_id");
+        std::fs::create_dir_all(steam_root.join("userdata/0")).unwrap();
+        std::fs::create_dir_all(steam_root.join("userdata/123")).unwrap();
+        std::fs::write(steam_root.join("userdata/456"), b"not a user").unwrap();
+
+        assert_eq!("123", find_user_id(&steam_root, None).unwrap());
+        assert_eq!("123", find_user_id(&steam_root, Some("123")).unwrap());
+
+        std::fs::create_dir_all(steam_root.join("userdata/789")).unwrap();
+        assert!(find_user_id(&steam_root, None).is_err());
+    }
+}
@@ -0,0 +1,80 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const STEAM_PROCESS_NAME: &str = "steam";
+const PROC_DIR: &str = "/proc";
+
+/// Returns the pid of a running Steam client. The pid files are checked first,
+/// then all processes in `/proc`.
+pub(crate) fn find_running_steam(pid_files: &[PathBuf]) -> Option<u32> {
+    find_running_steam_in(Path::new(PROC_DIR), pid_files)
+}
+
+/// Blocks until [`find_running_steam`] does not find a Steam client anymore.
+pub(crate) fn wait_for_steam_exit(pid_files: &[PathBuf]) {
+    while find_running_steam(pid_files).is_some() {
+        std::thread::sleep(Duration::from_secs(1));
+    }
+}
+
+fn find_running_steam_in(proc_dir: &Path, pid_files: &[PathBuf]) -> Option<u32> {
+    for pid_file in pid_files {
+        if let Some(pid) = std::fs::read_to_string(pid_file)
+            .ok()
+            .and_then(|content| content.trim().parse().ok())
+            && is_steam_process(proc_dir, pid)
+        {
+            return Some(pid);
+        }
+    }
+
+    let mut pids = std::fs::read_dir(proc_dir)
+        .ok()?
+        .filter_map(|e| e.ok()?.file_name().to_str()?.parse().ok())
+        .collect::<Vec<u32>>();
+    pids.sort();
+    pids.into_iter()
+        .find(|&pid| is_steam_process(proc_dir, pid))
+}
+
+fn is_steam_process(proc_dir: &Path, pid: u32) -> bool {
+    std::fs::read_to_string(proc_dir.join(pid.to_string()).join("comm"))
+        .is_ok_and(|comm| comm.trim_end() == STEAM_PROCESS_NAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::steam::running::find_running_steam_in;
+    use crate::utils::TempDir;
+
+    #[test]
+    fn test_find_running_steam() {
+        let dir = TempDir::new("proc");
+        let proc_dir = dir.join("proc");
+        for (pid, comm) in [
+            ("1", "systemd\n"),
+            ("20", "steamwebhelper\n"),
+            ("300", "bash\n"),
+        ] {
+            std::fs::create_dir_all(proc_dir.join(pid)).unwrap();
+            std::fs::write(proc_dir.join(pid).join("comm"), comm).unwrap();
+        }
+        std::fs::create_dir_all(proc_dir.join("self")).unwrap();
+        let pid_file = dir.join("steam.pid");
+        std::fs::write(&pid_file, "300\n").unwrap();
+        let pid_files = vec![dir.join("missing.pid"), pid_file.clone()];
+
+        assert_eq!(None, find_running_steam_in(&proc_dir, &pid_files));
+
+        std::fs::create_dir_all(proc_dir.join("4000")).unwrap();
+        std::fs::write(proc_dir.join("4000/comm"), "steam\n").unwrap();
+        assert_eq!(Some(4000), find_running_steam_in(&proc_dir, &pid_files));
+
+        std::fs::create_dir_all(proc_dir.join("50")).unwrap();
+        std::fs::write(proc_dir.join("50/comm"), "steam\n").unwrap();
+        std::fs::write(&pid_file, "4000").unwrap();
+        assert_eq!(Some(4000), find_running_steam_in(&proc_dir, &pid_files));
+        assert_eq!(Some(50), find_running_steam_in(&proc_dir, &[]));
+        assert_eq!(None, find_running_steam_in(&dir.join("missing"), &[]));
+    }
+}
@@ -0,0 +1,225 @@
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+const ACCOUNT_ID_MASK: u64 = 0xFFFF_FFFF;
+const INSTANCE_SHIFT: u32 = 32;
+const INSTANCE_MASK: u64 = 0xF_FFFF;
+const ACCOUNT_TYPE_SHIFT: u32 = 52;
+const ACCOUNT_TYPE_MASK: u64 = 0xF;
+const UNIVERSE_SHIFT: u32 = 56;
+
+const UNIVERSE_PUBLIC: u8 = 1;
+const ACCOUNT_TYPE_INDIVIDUAL: u8 = 1;
+const INSTANCE_DESKTOP: u32 = 1;
+
+/// Letters of the account types in the Steam3 form, indexed by account type.
+const ACCOUNT_TYPE_LETTERS: [char; 11] = ['I', 'U', 'M', 'G', 'A', 'P', 'C', 'g', 'T', ' ', 'a'];
+
+/// A SteamID in its 64-bit form. It converts from and to the 32-bit account id used for the
+/// `userdata` folders, the Steam2 form `STEAM_0:1:x` and the Steam3 form `[U:1:x]`.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct SteamId(u64);
+
+impl SteamId {
+    pub(crate) fn new(universe: u8, account_type: u8, instance: u32, account_id: u32) -> Self {
+        Self(
+            (universe as u64) << UNIVERSE_SHIFT
+                | (account_type as u64 & ACCOUNT_TYPE_MASK) << ACCOUNT_TYPE_SHIFT
+                | (instance as u64 & INSTANCE_MASK) << INSTANCE_SHIFT
+                | account_id as u64,
+        )
+    }
+
+    /// The id of an individual account in the public universe.
+    pub(crate) fn from_account_id(account_id: u32) -> Self {
+        Self::new(
+            UNIVERSE_PUBLIC,
+            ACCOUNT_TYPE_INDIVIDUAL,
+            INSTANCE_DESKTOP,
+            account_id,
+        )
+    }
+
+    pub(crate) fn from_steam_id_64(steam_id_64: u64) -> Self {
+        Self(steam_id_64)
+    }
+
+    pub(crate) fn steam_id_64(self) -> u64 {
+        self.0
+    }
+
+    /// The 32-bit id that names the folder of the user below `userdata`.
+    pub(crate) fn account_id(self) -> u32 {
+        (self.0 & ACCOUNT_ID_MASK) as u32
+    }
+
+    pub(crate) fn universe(self) -> u8 {
+        (self.0 >> UNIVERSE_SHIFT) as u8
+    }
+
+    pub(crate) fn account_type(self) -> u8 {
+        ((self.0 >> ACCOUNT_TYPE_SHIFT) & ACCOUNT_TYPE_MASK) as u8
+    }
+
+    pub(crate) fn instance(self) -> u32 {
+        ((self.0 >> INSTANCE_SHIFT) & INSTANCE_MASK) as u32
+    }
+
+    /// `STEAM_X:Y:Z`, with the public universe written as `0` like Steam itself does.
+    pub(crate) fn to_steam2(self) -> String {
+        let universe = match self.universe() {
+            UNIVERSE_PUBLIC => 0,
+            universe => universe,
+        };
+        format!(
+            "STEAM_{universe}:{}:{}",
+            self.account_id() & 1,
+            self.account_id() >> 1
+        )
+    }
+
+    /// `[U:1:x]`, with the instance appended for non-desktop individual accounts.
+    pub(crate) fn to_steam3(self) -> String {
+        let letter = ACCOUNT_TYPE_LETTERS
+            .get(self.account_type() as usize)
+            .copied()
+            .unwrap_or('I');
+        let mut rtn = format!("[{letter}:{}:{}", self.universe(), self.account_id());
+        if letter == 'U' && self.instance() != INSTANCE_DESKTOP {
+            rtn.push_str(&format!(":{}", self.instance()));
+        }
+        rtn.push(']');
+        rtn
+    }
+
+    fn parse_steam2(value: &str) -> anyhow::Result<Self> {
+        let parts: Vec<&str> = value.split(':').collect();
+        let [universe, y, z] = parts.as_slice() else {
+            return Err(anyhow!("Steam2 ids have the form STEAM_X:Y:Z"));
+        };
+        let universe = match universe.parse::<u8>()? {
+            0 => UNIVERSE_PUBLIC,
+            universe => universe,
+        };
+        let y: u32 = y.parse()?;
+        if y > 1 {
+            return Err(anyhow!("The Y part of a Steam2 id is 0 or 1"));
+        }
+        let z: u32 = z.parse()?;
+        let account_id = z
+            .checked_mul(2)
+            .and_then(|id| id.checked_add(y))
+            .ok_or(anyhow!("The account id of the Steam2 id is too large"))?;
+        Ok(Self::new(
+            universe,
+            ACCOUNT_TYPE_INDIVIDUAL,
+            INSTANCE_DESKTOP,
+            account_id,
+        ))
+    }
+
+    fn parse_steam3(value: &str) -> anyhow::Result<Self> {
+        let parts: Vec<&str> = value.split(':').collect();
+        let (letter, universe, account_id, instance) = match parts.as_slice() {
+            [letter, universe, account_id] => (letter, universe, account_id, None),
+            [letter, universe, account_id, instance] => {
+                (letter, universe, account_id, Some(instance.parse()?))
+            }
+            _ => return Err(anyhow!("Steam3 ids have the form [U:1:x]")),
+        };
+        let account_type = ACCOUNT_TYPE_LETTERS
+            .iter()
+            .position(|l| *l != ' ' && letter.chars().eq([*l]))
+            .ok_or(anyhow!("Unknown account type {letter}"))?;
+        let default_instance = if account_type == ACCOUNT_TYPE_INDIVIDUAL as usize {
+            INSTANCE_DESKTOP
+        } else {
+            0
+        };
+        Ok(Self::new(
+            universe.parse()?,
+            account_type as u8,
+            instance.unwrap_or(default_instance),
+            account_id.parse()?,
+        ))
+    }
+}
+
+impl Display for SteamId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.steam_id_64())
+    }
+}
+
+impl FromStr for SteamId {
+    type Err = anyhow::Error;
+
+    /// Parses a SteamID64, a 32-bit account id, a Steam2 or a Steam3 id.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let to_error = |err: anyhow::Error| anyhow!("{value} is not a valid SteamID - {err}");
+
+        if let Some(steam2) = value.strip_prefix("STEAM_") {
+            return Self::parse_steam2(steam2).map_err(to_error);
+        }
+        if let Some(steam3) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            return Self::parse_steam3(steam3).map_err(to_error);
+        }
+
+        let id: u64 = value
+            .parse()
+            .map_err(|_| anyhow!("{value} is not a valid SteamID"))?;
+        Ok(match u32::try_from(id) {
+            Ok(account_id) => Self::from_account_id(account_id),
+            Err(_) => Self::from_steam_id_64(id),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::steam::steam_id::SteamId;
+
+    #[test]
+    fn test_steam_id_forms() {
+        let steam_id = SteamId::from_steam_id_64(76561197960287930);
+
+        assert_eq!(22202, steam_id.account_id());
+        assert_eq!(1, steam_id.universe());
+        assert_eq!(1, steam_id.account_type());
+        assert_eq!(1, steam_id.instance());
+        assert_eq!("STEAM_0:0:11101", steam_id.to_steam2());
+        assert_eq!("[U:1:22202]", steam_id.to_steam3());
+        assert_eq!("76561197960287930", steam_id.to_string());
+        assert_eq!(steam_id, SteamId::from_account_id(22202));
+    }
+
+    #[test]
+    fn test_parse_steam_id() {
+        let steam_id = SteamId::from_steam_id_64(76561198000000001);
+
+        for value in [
+            "76561198000000001",
+            "39734273",
+            "STEAM_0:1:19867136",
+            "STEAM_1:1:19867136",
+            "[U:1:39734273]",
+            " [U:1:39734273] ",
+        ] {
+            assert_eq!(steam_id, value.parse::<SteamId>().unwrap(), "{value}");
+        }
+
+        let group: SteamId = "[g:1:4]".parse().unwrap();
+        assert_eq!(103582791429521412, group.steam_id_64());
+        assert_eq!("[g:1:4]", group.to_steam3());
+
+        for value in ["", "name", "STEAM_0:2:1", "STEAM_0:1", "[X:1:2]", "[U:1:x]"] {
+            assert!(value.parse::<SteamId>().is_err(), "{value}");
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use crate::shortcuts::Shortcuts;
+use crate::steam::login_users::LoginUsers;
+use crate::steam::steam_id::SteamId;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const GRID_IMAGE_EXTENSIONS: [&str; 5] = ["jpg", "jpeg", "png", "webp", "ico"];
+
+/// A user folder below `userdata` joined with the data Steam keeps in loginusers.vdf.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SteamUser {
+    pub user_id: String,
+    pub steam_id_64: Option<SteamId>,
+    pub steam_id_2: Option<String>,
+    pub steam_id_3: Option<String>,
+    pub account_name: Option<String>,
+    pub persona_name: Option<String>,
+    pub most_recent: bool,
+    pub last_login: Option<DateTime<Local>>,
+    pub shortcut_count: Option<usize>,
+    pub grid_image_count: usize,
+}
+
+impl SteamUser {
+    /// All users with a folder below `userdata` of the Steam root, sorted by user id.
+    pub(crate) fn list(steam_root: &Path) -> anyhow::Result<Vec<SteamUser>> {
+        let login_users = LoginUsers::from_vdf_file(&steam_root.join("config/loginusers.vdf"))
+            .unwrap_or_default();
+
+        let mut rtn = Vec::new();
+        for dir_entry in steam_root.join("userdata").read_dir()? {
+            let dir_entry = dir_entry?;
+            let Ok(user_id) = dir_entry.file_name().into_string() else {
+                continue;
+            };
+            if user_id == "0" || !dir_entry.path().is_dir() {
+                continue;
+            }
+
+            let login_user = login_users
+                .users
+                .iter()
+                .find(|u| u.account_id().to_string() == user_id);
+            let config_folder = dir_entry.path().join("config");
+
+            let steam_id: Option<SteamId> = login_user
+                .map(|u| u.steam_id)
+                .or_else(|| user_id.parse().ok());
+
+            rtn.push(SteamUser {
+                steam_id_64: steam_id,
+                steam_id_2: steam_id.map(SteamId::to_steam2),
+                steam_id_3: steam_id.map(SteamId::to_steam3),
+                account_name: login_user.map(|u| u.account_name.clone()),
+                persona_name: login_user.map(|u| u.persona_name.clone()),
+                most_recent: login_user.is_some_and(|u| u.most_recent),
+                last_login: login_user
+                    .and_then(|u| DateTime::from_timestamp(u.timestamp as i64, 0))
+                    .map(|t| t.with_timezone(&Local)),
+                shortcut_count: Shortcuts::from_path(&config_folder.join("shortcuts.vdf"))
+                    .map(|s| s.shortcuts.len()),
+                grid_image_count: count_grid_images(&config_folder.join("grid")),
+                user_id,
+            });
+        }
+        rtn.sort_by(|a, b| a.user_id.cmp(&b.user_id));
+
+        Ok(rtn)
+    }
+}
+
+fn count_grid_images(grid_folder: &Path) -> usize {
+    let Ok(read_dir) = grid_folder.read_dir() else {
+        return 0;
+    };
+    read_dir
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            e.path().extension().is_some_and(|extension| {
+                GRID_IMAGE_EXTENSIONS
+                    .iter()
+                    .any(|i| extension.eq_ignore_ascii_case(i))
+            })
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shortcuts::Shortcuts;
+    use crate::steam::steam_user::SteamUser;
+    use crate::utils::TempDir;
+
+    #[test]
+    fn test_list_users() {
+        let steam_root = TempDir::new("steam_user");
+        let config_folder = steam_root.join("userdata/39734273/config");
+        std::fs::create_dir_all(config_folder.join("grid")).unwrap();
+        std::fs::create_dir_all(steam_root.join("userdata/0")).unwrap();
+        std::fs::create_dir_all(steam_root.join("config")).unwrap();
+        std::fs::write(
+            steam_root.join("config/loginusers.vdf"),
+            "\"users\" { \"76561198000000001\" { \"AccountName\" \"first\" \"Timestamp\" \"1700000000\" } }",
+        )
+        .unwrap();
+        Shortcuts::default()
+            .save_to(&config_folder.join("shortcuts.vdf"))
+            .unwrap();
+        std::fs::write(config_folder.join("grid/1p.jpg"), b"").unwrap();
+        std::fs::write(config_folder.join("grid/1_logo.png"), b"").unwrap();
+        std::fs::write(config_folder.join("grid/notes.txt"), b"").unwrap();
+
+        let users = SteamUser::list(&steam_root).unwrap();
+
+        assert_eq!(1, users.len());
+        assert_eq!("39734273", users[0].user_id);
+        assert_eq!(
+            Some(76561198000000001),
+            users[0].steam_id_64.map(|i| i.steam_id_64())
+        );
+        assert_eq!(Some("STEAM_0:1:19867136"), users[0].steam_id_2.as_deref());
+        assert_eq!(Some("[U:1:39734273]"), users[0].steam_id_3.as_deref());
+        assert_eq!(Some("first".to_string()), users[0].account_name);
+        assert_eq!(Some(0), users[0].shortcut_count);
+        assert_eq!(2, users[0].grid_image_count);
+    }
+}
@@ -1,79 +1,22 @@
 use crate::config::Config;
-use crate::shortcuts::parsing_error::ParsingError;
 use crate::shortcuts::shortcut::ShortcutEntry;
 use crate::steam::get_best_steam_app_id;
 use crate::steamstatic::{download_600x900_2x, download_hero, download_logo};
+use anyhow::anyhow;
 use cosmic_text::{Attrs, Buffer, Color, FontSystem, Metrics, Shaping, SwashCache, Weight};
 use image::imageops::FilterType;
 use image::{GenericImageView, Pixel, RgbImage, RgbaImage};
 use resvg::{tiny_skia, usvg};
-use std::ops::AddAssign;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 
-pub(crate) fn fix_launch_options(launch_options: String) -> String {
-    let bytes = launch_options.as_bytes();
-    let mut tokens: Vec<&str> = Vec::new();
-
-    let mut pos = 0;
-    let mut start = 0;
-    let mut in_token = false;
-    let mut skip_next = false;
-    let mut in_quotation = false;
-
-    while pos < bytes.len() {
-        if skip_next {
-            skip_next = false;
-            continue;
-        }
-
-        let b = bytes[pos];
-
-        if b == b'\\' {
-            skip_next = true;
-            continue;
-        }
-
-        if in_token {
-            if in_quotation {
-                if b == b'"' {
-                    in_token = false;
-                    in_quotation = false;
-                    tokens.push(&launch_options[start..pos]);
-                }
-            } else if b == b' ' {
-                in_token = false;
-                tokens.push(&launch_options[start..pos]);
-            }
-        } else if b == b'"' {
-            in_token = true;
-            in_quotation = true;
-            start = pos + 1;
-        } else if b != b' ' {
-            in_token = true;
-            start = pos;
-        }
-        pos += 1
-    }
-
-    tokens.push(&launch_options[start..]);
-    let mut rtn = String::new();
-
-    let mut is_first = true;
-
-    for token in tokens {
-        if token.is_empty() || token == "%f" {
-            continue;
-        }
-        if is_first {
-            is_first = false;
-        } else {
-            rtn.push(' ');
-        }
-        rtn.push('"');
-        rtn.push_str(token);
-        rtn.push('"');
-    }
-    rtn
+/// Quotes every argument, the way the launch options of Flatpak shortcuts are stored.
+pub(crate) fn quote_launch_options(arguments: &[String]) -> String {
+    arguments
+        .iter()
+        .map(|argument| double_quote(argument))
+        .collect::<Vec<String>>()
+        .join(" ")
 }
 
 pub(crate) fn try_download_grid_files(
@@ -117,130 +60,58 @@ pub(crate) fn try_download_grid_files(
     ))
 }
 
-pub(crate) fn add_integer_to_shortcut(
-    shortcut_entry_option: Option<&mut ShortcutEntry>,
-    key: String,
-    value: u32,
-) -> Result<(), ParsingError> {
-    if let Some(shortcut_entry) = shortcut_entry_option {
-        match key.as_str() {
-            "appid" => shortcut_entry.appid = value,
-            "IsHidden" => shortcut_entry.is_hidden = value,
-            "AllowDesktopConfig" => shortcut_entry.allow_desktop_config = value,
-            "AllowOverlay" => shortcut_entry.allow_overlay = value,
-            "OpenVR" => shortcut_entry.open_vr = value,
-            "Devkit" => shortcut_entry.devkit = value,
-            "DevkitOverrideAppID" => shortcut_entry.devkit_override_app_id = value,
-            "LastPlayTime" => shortcut_entry.last_play_time = value,
-            _ => return Err(ParsingError::UnknownIntegerKey(key)),
-        }
-    } else {
-        return Err(ParsingError::CurrentShortcutEntryIsEmpty);
-    }
-    Ok(())
-}
-
-pub(crate) fn add_string_to_shortcut(
-    shortcut_entry_option: Option<&mut ShortcutEntry>,
-    key: String,
-    value: String,
-) -> Result<(), ParsingError> {
-    if let Some(shortcut_entry) = shortcut_entry_option {
-        match key.as_str() {
-            "AppName" => shortcut_entry.app_name = value,
-            "Exe" => shortcut_entry.exe = value,
-            "StartDir" => shortcut_entry.start_dir = value,
-            "icon" => shortcut_entry.icon = value,
-            "ShortcutPath" => shortcut_entry.shortcut_path = value,
-            "LaunchOptions" => shortcut_entry.launch_options = value,
-            "DevkitGameID" => shortcut_entry.devkit_game_id = value,
-            "FlatpakAppID" => shortcut_entry.flatpak_app_id = value,
-            "sortas" => shortcut_entry.sort_as = value,
-            _ => return Err(ParsingError::UnknownStringKey(key)),
-        }
-    } else {
-        return Err(ParsingError::CurrentShortcutEntryIsEmpty);
-    }
-    Ok(())
-}
-
-pub(crate) fn insert_entry_string_map(bytes: &mut Vec<u8>, key: &str) {
-    bytes.push(0x00);
-    insert_str_into_bytes(bytes, key);
-    bytes.push(0x08);
-}
-
-pub(crate) fn insert_entry_string_integer(bytes: &mut Vec<u8>, key: &str, value: u32) {
-    bytes.push(0x02);
-    insert_str_into_bytes(bytes, key);
-    insert_integer_into_bytes(bytes, value);
-}
-
-pub(crate) fn insert_entry_string_string(bytes: &mut Vec<u8>, key: &str, value: &str) {
-    bytes.push(0x01);
-    insert_str_into_bytes(bytes, key);
-    insert_str_into_bytes(bytes, value);
+pub(crate) fn generate_app_id(exe: &str, app_name: &str) -> u32 {
+    const CRC_32: crc::Crc<u32> = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+    let crc = CRC_32.checksum(format!("{exe}{app_name}").as_bytes());
+    crc | 0x80000000
 }
 
-pub(crate) fn insert_integer_into_bytes(bytes: &mut Vec<u8>, value: u32) {
-    let integer_bytes: [u8; 4] = value.to_le_bytes();
-    bytes.push(integer_bytes[0]);
-    bytes.push(integer_bytes[1]);
-    bytes.push(integer_bytes[2]);
-    bytes.push(integer_bytes[3]);
+/// The appid Steam assigns when a non-Steam game is added: the CRC32 of the quoted exe
+/// followed by the app name.
+pub(crate) fn generate_steam_app_id(exe: &str, app_name: &str) -> u32 {
+    generate_app_id(&quote_path(exe), app_name)
 }
 
-pub(crate) fn insert_str_into_bytes(bytes: &mut Vec<u8>, value: &str) {
-    for &i in value.as_bytes() {
-        bytes.push(i);
-    }
-    bytes.push(0x00);
+/// The 64-bit id of a shortcut used by `steam://rungameid/` and legacy grid file names.
+pub(crate) fn generate_game_id(appid: u32) -> u64 {
+    (appid as u64) << 32 | 0x02000000
 }
 
-pub(crate) fn insert_string_into_bytes(bytes: &mut Vec<u8>, value: String) {
-    for &i in value.as_bytes() {
-        bytes.push(i);
+/// Quotes a launch option if the shell would otherwise split or expand it.
+pub(crate) fn quote_argument(argument: &str) -> String {
+    let is_plain = !argument.is_empty()
+        && argument
+            .chars()
+            .all(|c| c.is_alphanumeric() || "-_=+./:,@%".contains(c));
+    if is_plain {
+        return argument.to_string();
     }
-    bytes.push(0x00);
+    double_quote(argument)
 }
 
-pub(crate) fn try_read_string(position: &mut usize, bytes: &[u8]) -> Result<String, ParsingError> {
-    let mut rtn = String::new();
-
-    while *position < bytes.len() {
-        if bytes[*position] == 0x00 {
-            position.add_assign(1);
-            return Ok(rtn);
+/// Wraps the argument in double quotes, escaping what the shell expands inside of them.
+fn double_quote(argument: &str) -> String {
+    let mut rtn = String::from('"');
+    for c in argument.chars() {
+        if matches!(c, '"' | '\\' | '$' | '`') {
+            rtn.push('\\');
         }
-        rtn.push(bytes[*position] as char);
-        position.add_assign(1);
+        rtn.push(c);
     }
-
-    Err(ParsingError::FailedToParseString)
+    rtn.push('"');
+    rtn
 }
 
-pub(crate) fn try_read_integer(position: &mut usize, bytes: &[u8]) -> Result<u32, ParsingError> {
-    if *position + 4 >= bytes.len() {
-        return Err(ParsingError::FailedToParseInteger);
+/// Wraps the path in double quotes the way Steam stores `Exe` and `StartDir`.
+pub(crate) fn quote_path(path: &str) -> String {
+    if path.len() >= 2 && path.starts_with('"') && path.ends_with('"') {
+        return path.to_string();
     }
-    let used_bytes: [u8; 4] = [
-        bytes[*position],
-        bytes[*position + 1],
-        bytes[*position + 2],
-        bytes[*position + 3],
-    ];
-    let rtn = u32::from_le_bytes(used_bytes);
-    *position += 4;
-    Ok(rtn)
-}
-
-pub(crate) fn generate_app_id(exe: &str, app_name: &str) -> u32 {
-    const CRC_32: crc::Crc<u32> = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
-    let crc = CRC_32.checksum(format!("{exe}{app_name}").as_bytes());
-    crc | 0x80000000
+    format!("\"{path}\"")
 }
 
-pub(crate) fn create_png_from_svg(from: &Path, to: &Path) {
+/// Renders the SVG as a 256x256 PNG.
+pub(crate) fn create_png_from_svg(from: &Path, to: &Path) -> anyhow::Result<()> {
     let tree = {
         let mut opt = usvg::Options {
             // Get file's absolute directory.
@@ -251,12 +122,12 @@ pub(crate) fn create_png_from_svg(from: &Path, to: &Path) {
         };
         opt.fontdb_mut().load_system_fonts();
 
-        let svg_data = std::fs::read(from).unwrap();
-        usvg::Tree::from_data(&svg_data, &opt).unwrap()
+        let svg_data = std::fs::read(from)?;
+        usvg::Tree::from_data(&svg_data, &opt)?
     };
 
     let pixmap_size = tree.size().to_int_size();
-    let mut pixmap = tiny_skia::Pixmap::new(256, 256).unwrap();
+    let mut pixmap = tiny_skia::Pixmap::new(256, 256).ok_or(anyhow!("Could not create the PNG"))?;
     let sx = 256.0f32 / pixmap_size.width() as f32;
     let sy = 256.0f32 / pixmap_size.height() as f32;
     resvg::render(
@@ -264,7 +135,8 @@ pub(crate) fn create_png_from_svg(from: &Path, to: &Path) {
         tiny_skia::Transform::from_scale(sx, sy),
         &mut pixmap.as_mut(),
     );
-    pixmap.save_png(to).unwrap();
+    pixmap.save_png(to)?;
+    Ok(())
 }
 
 pub(crate) fn draw_steam_logo_with_text(text: &str, to: &Path) {
@@ -366,8 +238,136 @@ pub(crate) fn draw_steam_logo_with_text(text: &str, to: &Path) {
     image.save(to).unwrap();
 }
 
-pub(crate) fn create_grid_for_shortcut(shortcut: &ShortcutEntry, grid_path: &Path) {
-    let icon = image::open(&shortcut.icon).unwrap();
+/// Artwork of the shortcut in the grid folder: `{appid}`, `{appid}p`, `{appid}_hero`,
+/// `{appid}_logo` and the legacy `{game_id}` with any extension.
+pub(crate) fn find_grid_files(shortcut: &ShortcutEntry, grid_path: &Path) -> Vec<PathBuf> {
+    let stems = grid_file_stems(shortcut);
+    let Ok(read_dir) = grid_path.read_dir() else {
+        return Vec::new();
+    };
+    let mut rtn: Vec<PathBuf> = read_dir
+        .filter_map(|e| Some(e.ok()?.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .is_some_and(|stem| stems.iter().any(|s| s == stem))
+        })
+        .collect();
+    rtn.sort();
+    rtn
+}
+
+/// Renames the artwork of `from` to the file names of the appid of `to`.
+/// Returns the old and new path of every moved file. Nothing is moved if a new path
+/// already exists, and the moved files are put back if a rename fails.
+pub(crate) fn move_grid_files(
+    from: &ShortcutEntry,
+    to: &ShortcutEntry,
+    grid_path: &Path,
+) -> std::io::Result<Vec<(PathBuf, PathBuf)>> {
+    let from_stems = grid_file_stems(from);
+    let to_stems = grid_file_stems(to);
+
+    let mut moves = Vec::new();
+    for path in find_grid_files(from, grid_path) {
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        let Some(index) = from_stems.iter().position(|s| s == stem) else {
+            continue;
+        };
+        let mut new_path = grid_path.join(&to_stems[index]);
+        if let Some(extension) = path.extension() {
+            new_path.set_extension(extension);
+        }
+        if new_path != path {
+            moves.push((path, new_path));
+        }
+    }
+    if let Some((_, new_path)) = moves.iter().find(|(_, new_path)| new_path.exists()) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            format!("{} already exists", new_path.display()),
+        ));
+    }
+
+    let mut rtn = Vec::new();
+    for (path, new_path) in moves {
+        if let Err(err) = std::fs::rename(&path, &new_path) {
+            for (old_path, moved_path) in rtn.iter().rev() {
+                std::fs::rename(moved_path, old_path).ok();
+            }
+            return Err(err);
+        }
+        rtn.push((path, new_path));
+    }
+    Ok(rtn)
+}
+
+fn grid_file_stems(shortcut: &ShortcutEntry) -> [String; 5] {
+    [
+        shortcut.appid.to_string(),
+        format!("{}p", shortcut.appid),
+        format!("{}_hero", shortcut.appid),
+        format!("{}_logo", shortcut.appid),
+        shortcut.game_id().to_string(),
+    ]
+}
+
+/// Resolves the executable to an absolute path. Bare names are searched in `PATH`. Symlinks
+/// are kept, as multi-call binaries and alternatives depend on the name they are run by.
+pub(crate) fn find_executable(exe: &Path) -> anyhow::Result<PathBuf> {
+    let candidates: Vec<PathBuf> = if exe.components().count() > 1 || exe.is_absolute() {
+        vec![exe.to_path_buf()]
+    } else {
+        std::env::var_os("PATH")
+            .map(|path| std::env::split_paths(&path).map(|p| p.join(exe)).collect())
+            .unwrap_or_default()
+    };
+
+    let Some(rtn) = candidates.into_iter().find(|c| c.is_file()) else {
+        return Err(anyhow!("{} does not exist", exe.display()));
+    };
+    if !is_executable(&rtn) {
+        return Err(anyhow!("{} is not executable", rtn.display()));
+    }
+    Ok(std::path::absolute(rtn)?)
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Asks a yes/no question on the terminal. Anything but `y` or `yes` is a no.
+pub(crate) fn confirm(question: &str) -> bool {
+    print!("{question} [y/N] ");
+    std::io::stdout().flush().ok();
+    let mut answer = String::new();
+    if std::io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Generates the portrait, hero and logo artwork of the shortcut from its icon, which has
+/// to be an image format Steam can show.
+pub(crate) fn create_grid_for_shortcut(
+    shortcut: &ShortcutEntry,
+    grid_path: &Path,
+) -> anyhow::Result<()> {
+    let icon = image::open(&shortcut.icon)
+        .map_err(|err| anyhow!("Could not open the icon {} - {err}", shortcut.icon))?;
     let mut img = icon.resize(400, 400, FilterType::Nearest);
     let x = (600 - img.width()) / 2;
     let y = (900 - img.height()) / 2;
@@ -388,12 +388,9 @@ pub(crate) fn create_grid_for_shortcut(shortcut: &ShortcutEntry, grid_path: &Pat
 
     let p_image_path = grid_path.join(format!("{}p.jpg", shortcut.appid));
 
-    let parent = p_image_path.parent().unwrap();
-    std::fs::create_dir(parent).ok();
+    std::fs::create_dir_all(grid_path)?;
 
-    final_image
-        .save_with_format(p_image_path, image::ImageFormat::Jpeg)
-        .unwrap();
+    final_image.save_with_format(p_image_path, image::ImageFormat::Jpeg)?;
 
     let mut blur = icon.resize_to_fill(1920, 620, FilterType::Nearest);
     blur = blur.fast_blur(100.0);
@@ -403,11 +400,9 @@ pub(crate) fn create_grid_for_shortcut(shortcut: &ShortcutEntry, grid_path: &Pat
     image::imageops::overlay(&mut blur, &img, x as i64, y as i64);
     let rgb = blur.to_rgb8();
     let base_image_path = grid_path.join(format!("{}.jpg", shortcut.appid));
-    rgb.save_with_format(base_image_path, image::ImageFormat::Jpeg)
-        .unwrap();
+    rgb.save_with_format(base_image_path, image::ImageFormat::Jpeg)?;
     let hero_image_path = grid_path.join(format!("{}_hero.jpg", shortcut.appid));
-    rgb.save_with_format(hero_image_path, image::ImageFormat::Jpeg)
-        .unwrap();
+    rgb.save_with_format(hero_image_path, image::ImageFormat::Jpeg)?;
 
     // Logo
     // let mut logo = image::RgbaImage::new(640, 360);
@@ -454,29 +449,190 @@ pub(crate) fn create_grid_for_shortcut(shortcut: &ShortcutEntry, grid_path: &Pat
 
     let logo_path = grid_path.join(format!("{}_logo.png", shortcut.appid));
     draw_steam_logo_with_text(&shortcut.app_name, &logo_path);
+    Ok(())
+}
+
+/// A new folder below the temporary folder for a test, removed again when dropped.
+#[cfg(test)]
+pub(crate) struct TempDir(PathBuf);
+
+#[cfg(test)]
+impl TempDir {
+    pub(crate) fn new(name: &str) -> Self {
+        static COUNTER: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        let count = COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!(
+            "steam_shortcut_{name}_{}_{count}",
+            std::process::id()
+        ));
+        std::fs::remove_dir_all(&path).ok();
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+}
+
+#[cfg(test)]
+impl std::ops::Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.0).ok();
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::path::Path;
 
-    use crate::utils::{draw_steam_logo_with_text, fix_launch_options};
+    use crate::desktop_file::{ExecContext, expand_exec, flatpak_run_arguments};
+    use crate::shortcuts::shortcut::ShortcutEntry;
+    use crate::utils::{
+        TempDir, create_grid_for_shortcut, draw_steam_logo_with_text, find_executable,
+        generate_app_id, generate_game_id, generate_steam_app_id, move_grid_files, quote_argument,
+        quote_launch_options, quote_path,
+    };
+
+    #[test]
+    fn test_quote_argument() {
+        assert_eq!("--fullscreen", quote_argument("--fullscreen"));
+        assert_eq!("org.mozilla.firefox", quote_argument("org.mozilla.firefox"));
+        assert_eq!("\"\"", quote_argument(""));
+        assert_eq!("\"two words\"", quote_argument("two words"));
+        assert_eq!("\"\\$HOME \\\"x\\\"\"", quote_argument("$HOME \"x\""));
+    }
+
+    #[test]
+    fn test_find_executable() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new("executable");
+        let script = dir.join("game.sh");
+        let text = dir.join("notes.txt");
+        std::fs::write(&script, "#!/bin/sh").unwrap();
+        std::fs::write(&text, "").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::fs::set_permissions(&text, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        let link = dir.join("link");
+        std::os::unix::fs::symlink(&script, &link).unwrap();
+
+        assert_eq!(script, find_executable(&script).unwrap());
+        assert_eq!(link, find_executable(&link).unwrap());
+        assert!(find_executable(&text).is_err());
+        assert!(find_executable(&dir.join("missing")).is_err());
+        assert!(find_executable(Path::new("sh")).unwrap().is_absolute());
+    }
+
+    #[test]
+    fn test_create_grid_for_unreadable_icon() {
+        let dir = TempDir::new("grid");
+        let icon = dir.join("icon.svg");
+        std::fs::write(&icon, "<svg/>").unwrap();
+        let shortcut = ShortcutEntry {
+            appid: 1,
+            icon: icon.to_string_lossy().to_string(),
+            ..Default::default()
+        };
+
+        assert!(create_grid_for_shortcut(&shortcut, &dir.join("grid")).is_err());
+        assert!(!dir.join("grid").exists());
+    }
+
+    #[test]
+    fn test_move_grid_files() {
+        let dir = TempDir::new("move_grid");
+        let from = ShortcutEntry {
+            appid: 1,
+            ..Default::default()
+        };
+        let to = ShortcutEntry {
+            appid: 2,
+            ..Default::default()
+        };
+        std::fs::write(dir.join("1p.jpg"), b"portrait").unwrap();
+        std::fs::write(dir.join("1_hero.png"), b"hero").unwrap();
+        std::fs::write(dir.join("2_hero.png"), b"taken").unwrap();
+
+        assert!(move_grid_files(&from, &to, &dir).is_err());
+        assert!(dir.join("1p.jpg").is_file());
+        assert!(!dir.join("2p.jpg").exists());
+        assert_eq!(
+            b"taken".to_vec(),
+            std::fs::read(dir.join("2_hero.png")).unwrap()
+        );
+
+        std::fs::remove_file(dir.join("2_hero.png")).unwrap();
+        let moved = move_grid_files(&from, &to, &dir).unwrap();
+        assert_eq!(2, moved.len());
+        assert_eq!(
+            b"portrait".to_vec(),
+            std::fs::read(dir.join("2p.jpg")).unwrap()
+        );
+        assert_eq!(
+            b"hero".to_vec(),
+            std::fs::read(dir.join("2_hero.png")).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_steam_app_id() {
+        let appid = generate_steam_app_id("/usr/bin/flatpak", "Firefox");
+
+        assert_eq!(4201274167, appid);
+        assert_eq!(
+            appid,
+            generate_steam_app_id("\"/usr/bin/flatpak\"", "Firefox")
+        );
+        assert_ne!(appid, generate_app_id("/usr/bin/flatpak", "Firefox"));
+        assert_eq!(18044335148828196864, generate_game_id(appid));
+        assert_eq!("\"/usr/bin\"", quote_path("/usr/bin"));
+        assert_eq!("\"\"", quote_path(""));
+    }
 
     #[test]
     fn test_launch_options_fixer() {
-        let launch_options = "run --branch=stable --arch=x86_64 --command=boxy-svg --file-forwarding com.boxy_svg.BoxySVG @@ %f @@";
-        let result = fix_launch_options(launch_options.to_string());
+        let launch_options = |exec: &str| {
+            let arguments = expand_exec(exec, &ExecContext::default()).unwrap();
+            quote_launch_options(&flatpak_run_arguments(&arguments, "com.boxy_svg.BoxySVG"))
+        };
+        let result = launch_options(
+            "/usr/bin/flatpak run --branch=stable --arch=x86_64 --command=boxy-svg --file-forwarding com.boxy_svg.BoxySVG @@ %f @@",
+        );
         println!("{}", &result);
         assert_eq!(
             r#""run" "--branch=stable" "--arch=x86_64" "--command=boxy-svg" "--file-forwarding" "com.boxy_svg.BoxySVG" "@@" "@@""#,
             result.as_str()
-        )
+        );
+        assert_eq!(
+            r#""run" "--command=boxy-svg" "com.boxy_svg.BoxySVG" "C:\\" "\$HOME""#,
+            launch_options(r#"boxy-svg C:\ "\$HOME""#)
+        );
+        assert_eq!(
+            r#""--user" "run" "--command=sh" "com.boxy_svg.BoxySVG" "-c" "echo \"rundir\"""#,
+            launch_options(
+                r#"flatpak --user run --command=sh com.boxy_svg.BoxySVG -c "echo \"rundir\"""#
+            )
+        );
     }
 
     #[test]
     fn draw_text_with_cosmic() {
-        draw_steam_logo_with_text("Heroic Games Launcher", &Path::new("target/result.png"));
-        draw_steam_logo_with_text("PG Admin 4", &Path::new("target/result2.png"));
-        draw_steam_logo_with_text("Minus Games", &Path::new("target/result3.png"));
+        draw_steam_logo_with_text("Heroic Games Launcher", Path::new("target/result.png"));
+        draw_steam_logo_with_text("PG Admin 4", Path::new("target/result2.png"));
+        draw_steam_logo_with_text("Minus Games", Path::new("target/result3.png"));
     }
 }
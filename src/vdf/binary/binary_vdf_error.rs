@@ -0,0 +1,54 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// An error while reading a binary VDF document, with the byte offset it occurred at
+/// and the path of keys (separated by `/`) that was being parsed.
+#[derive(Debug)]
+pub struct BinaryVdfError {
+    pub kind: BinaryVdfErrorKind,
+    pub offset: usize,
+    pub key: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum BinaryVdfErrorKind {
+    InvalidNodeType(u8),
+    FailedToParseString,
+    FailedToParseWideString,
+    FailedToParseNumber,
+    MapNotClosed,
+    TooDeeplyNested,
+    TrailingBytes,
+}
+
+impl Display for BinaryVdfErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BinaryVdfErrorKind::InvalidNodeType(node_type) => {
+                write!(f, "invalid node type 0x{node_type:02X}")
+            }
+            BinaryVdfErrorKind::FailedToParseString => write!(f, "string is not terminated"),
+            BinaryVdfErrorKind::FailedToParseWideString => write!(f, "wide string is truncated"),
+            BinaryVdfErrorKind::FailedToParseNumber => write!(f, "number is truncated"),
+            BinaryVdfErrorKind::MapNotClosed => write!(f, "map is not closed"),
+            BinaryVdfErrorKind::TooDeeplyNested => write!(f, "maps are nested too deeply"),
+            BinaryVdfErrorKind::TrailingBytes => write!(f, "unexpected bytes after the root map"),
+        }
+    }
+}
+
+impl Display for BinaryVdfError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.key.is_empty() {
+            write!(f, "{} at byte {}", self.kind, self.offset)
+        } else {
+            write!(
+                f,
+                "{} at byte {} in \"{}\"",
+                self.kind, self.offset, self.key
+            )
+        }
+    }
+}
+
+impl Error for BinaryVdfError {}
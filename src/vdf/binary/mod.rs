@@ -0,0 +1,286 @@
+pub(crate) mod binary_vdf_error;
+pub(crate) mod node;
+
+use crate::vdf::binary::binary_vdf_error::{BinaryVdfError, BinaryVdfErrorKind};
+use crate::vdf::binary::node::{
+    Entry, Node, TYPE_COLOR, TYPE_END, TYPE_FLOAT, TYPE_INT32, TYPE_INT64, TYPE_MAP, TYPE_PTR,
+    TYPE_STRING, TYPE_UINT64, TYPE_WSTRING,
+};
+
+/// Deeper maps are rejected so that corrupt input cannot overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+/// Reads a whole binary VDF document. The root is a map that is closed by a final end byte.
+/// Any input, including truncated or corrupt documents, results in an error instead of a panic.
+pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Vec<Entry>, BinaryVdfError> {
+    let mut reader = Reader {
+        bytes,
+        position: 0,
+        path: Vec::new(),
+    };
+    let rtn = reader.read_map()?;
+    if reader.position != bytes.len() {
+        return Err(reader.error(BinaryVdfErrorKind::TrailingBytes));
+    }
+    Ok(rtn)
+}
+
+pub(crate) fn to_bytes(entries: &[Entry]) -> Vec<u8> {
+    let mut rtn = Vec::new();
+    write_map(&mut rtn, entries);
+    rtn
+}
+
+/// Number of bytes the entry takes up once written, including its type byte and key.
+pub(crate) fn encoded_len(entry: &Entry) -> usize {
+    let value_len = match &entry.value {
+        Node::Map(entries) => entries.iter().map(encoded_len).sum::<usize>() + 1,
+        Node::String(value) => encode_string(value).len() + 1,
+        Node::Int32(_) | Node::Float(_) | Node::Ptr(_) | Node::Color(_) => 4,
+        Node::WString(value) => 2 + value.len() * 2,
+        Node::UInt64(_) | Node::Int64(_) => 8,
+    };
+    header_len(entry) + value_len
+}
+
+/// Number of bytes of the type byte and key in front of the value of the entry.
+pub(crate) fn header_len(entry: &Entry) -> usize {
+    1 + encode_string(&entry.key).len() + 1
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    path: Vec<String>,
+}
+
+impl Reader<'_> {
+    fn error(&self, kind: BinaryVdfErrorKind) -> BinaryVdfError {
+        BinaryVdfError {
+            kind,
+            offset: self.position,
+            key: self.path.join("/"),
+        }
+    }
+
+    /// Reads the entries of a map up to and including its end byte.
+    fn read_map(&mut self) -> Result<Vec<Entry>, BinaryVdfError> {
+        if self.path.len() > MAX_DEPTH {
+            return Err(self.error(BinaryVdfErrorKind::TooDeeplyNested));
+        }
+
+        let mut rtn = Vec::new();
+
+        while let Some(&node_type) = self.bytes.get(self.position) {
+            if node_type == TYPE_END {
+                self.position += 1;
+                return Ok(rtn);
+            }
+
+            if !matches!(
+                node_type,
+                TYPE_MAP
+                    | TYPE_STRING
+                    | TYPE_INT32
+                    | TYPE_FLOAT
+                    | TYPE_PTR
+                    | TYPE_WSTRING
+                    | TYPE_COLOR
+                    | TYPE_UINT64
+                    | TYPE_INT64
+            ) {
+                return Err(self.error(BinaryVdfErrorKind::InvalidNodeType(node_type)));
+            }
+            self.position += 1;
+
+            let key = self.read_string()?;
+            self.path.push(key);
+            let value = match node_type {
+                TYPE_MAP => Node::Map(self.read_map()?),
+                TYPE_STRING => Node::String(self.read_string()?),
+                TYPE_INT32 => Node::Int32(i32::from_le_bytes(self.read_array()?)),
+                TYPE_FLOAT => Node::Float(f32::from_le_bytes(self.read_array()?)),
+                TYPE_PTR => Node::Ptr(u32::from_le_bytes(self.read_array()?)),
+                TYPE_WSTRING => Node::WString(self.read_wide_string()?),
+                TYPE_COLOR => Node::Color(self.read_array()?),
+                TYPE_UINT64 => Node::UInt64(u64::from_le_bytes(self.read_array()?)),
+                _ => Node::Int64(i64::from_le_bytes(self.read_array()?)),
+            };
+            let key = self.path.pop().unwrap_or_default();
+
+            rtn.push(Entry { key, value });
+        }
+
+        Err(self.error(BinaryVdfErrorKind::MapNotClosed))
+    }
+
+    fn read_string(&mut self) -> Result<String, BinaryVdfError> {
+        let start = self.position;
+        let length = self.bytes[start..]
+            .iter()
+            .position(|&b| b == 0x00)
+            .ok_or_else(|| self.error(BinaryVdfErrorKind::FailedToParseString))?;
+        self.position += length + 1;
+        Ok(decode_string(&self.bytes[start..start + length]))
+    }
+
+    /// Wide strings are stored as a 16-bit length followed by that many UTF-16 code units.
+    fn read_wide_string(&mut self) -> Result<Vec<u16>, BinaryVdfError> {
+        let to_wide_string_error = |mut err: BinaryVdfError| {
+            err.kind = BinaryVdfErrorKind::FailedToParseWideString;
+            err
+        };
+        let length = u16::from_le_bytes(self.read_array().map_err(to_wide_string_error)?);
+        let mut rtn = Vec::new();
+        for _ in 0..length {
+            let unit = self.read_array().map_err(to_wide_string_error)?;
+            rtn.push(u16::from_le_bytes(unit));
+        }
+        Ok(rtn)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], BinaryVdfError> {
+        let rtn: [u8; N] = self
+            .bytes
+            .get(self.position..self.position + N)
+            .and_then(|slice| slice.try_into().ok())
+            .ok_or_else(|| self.error(BinaryVdfErrorKind::FailedToParseNumber))?;
+        self.position += N;
+        Ok(rtn)
+    }
+}
+
+/// Writes the entries of a map followed by its end byte.
+fn write_map(bytes: &mut Vec<u8>, entries: &[Entry]) {
+    for entry in entries {
+        bytes.push(entry.value.type_byte());
+        write_string(bytes, &entry.key);
+        match &entry.value {
+            Node::Map(entries) => write_map(bytes, entries),
+            Node::String(value) => write_string(bytes, value),
+            Node::Int32(value) => bytes.extend_from_slice(&value.to_le_bytes()),
+            Node::Float(value) => bytes.extend_from_slice(&value.to_le_bytes()),
+            Node::Ptr(value) => bytes.extend_from_slice(&value.to_le_bytes()),
+            Node::WString(value) => write_wide_string(bytes, value),
+            Node::Color(value) => bytes.extend_from_slice(value),
+            Node::UInt64(value) => bytes.extend_from_slice(&value.to_le_bytes()),
+            Node::Int64(value) => bytes.extend_from_slice(&value.to_le_bytes()),
+        }
+    }
+    bytes.push(TYPE_END);
+}
+
+fn write_string(bytes: &mut Vec<u8>, value: &str) {
+    bytes.extend_from_slice(&encode_string(value));
+    bytes.push(0x00);
+}
+
+/// Invalid UTF-8 bytes, which are always 0x80 or above, are mapped to the last 128 code
+/// points of the private use area, so that [`encode_string`] can restore them and strings
+/// round-trip unchanged. A real character in that range is mapped the same way byte by byte.
+const INVALID_BYTE_BASE: u32 = 0x10FF00;
+const ESCAPED: std::ops::RangeInclusive<u32> = INVALID_BYTE_BASE + 0x80..=INVALID_BYTE_BASE + 0xFF;
+
+fn decode_string(bytes: &[u8]) -> String {
+    let mut rtn = String::with_capacity(bytes.len());
+    let escape = |rtn: &mut String, b: u8| rtn.extend(char::from_u32(INVALID_BYTE_BASE + b as u32));
+    for chunk in bytes.utf8_chunks() {
+        for c in chunk.valid().chars() {
+            if ESCAPED.contains(&(c as u32)) {
+                let mut buffer = [0; 4];
+                for &b in c.encode_utf8(&mut buffer).as_bytes() {
+                    escape(&mut rtn, b);
+                }
+            } else {
+                rtn.push(c);
+            }
+        }
+        for &b in chunk.invalid() {
+            escape(&mut rtn, b);
+        }
+    }
+    rtn
+}
+
+fn encode_string(value: &str) -> Vec<u8> {
+    let mut rtn = Vec::with_capacity(value.len());
+    let mut buffer = [0; 4];
+    for c in value.chars() {
+        if ESCAPED.contains(&(c as u32)) {
+            rtn.push((c as u32 - INVALID_BYTE_BASE) as u8);
+        } else {
+            rtn.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+        }
+    }
+    rtn
+}
+
+fn write_wide_string(bytes: &mut Vec<u8>, value: &[u16]) {
+    bytes.extend_from_slice(&(value.len() as u16).to_le_bytes());
+    for unit in value {
+        bytes.extend_from_slice(&unit.to_le_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vdf::binary::node::{Entry, Node};
+    use crate::vdf::binary::{from_bytes, to_bytes};
+
+    #[test]
+    fn test_all_node_types_round_trip() {
+        let entries = vec![Entry::new(
+            "root",
+            Node::Map(vec![
+                Entry::new("map", Node::Map(vec![])),
+                Entry::new("string", Node::String("value".to_string())),
+                Entry::new("int32", Node::Int32(-1)),
+                Entry::new("float", Node::Float(1.5)),
+                Entry::new("ptr", Node::Ptr(0xDEADBEEF)),
+                Entry::new("wstring", Node::WString("wide".encode_utf16().collect())),
+                Entry::new("color", Node::Color([1, 2, 3, 4])),
+                Entry::new("uint64", Node::UInt64(u64::MAX)),
+                Entry::new("int64", Node::Int64(i64::MIN)),
+            ]),
+        )];
+
+        let bytes = to_bytes(&entries);
+        let parsed = from_bytes(&bytes).unwrap();
+
+        assert_eq!(entries, parsed);
+        assert_eq!(bytes, to_bytes(&parsed));
+    }
+
+    #[test]
+    fn test_utf8_strings() {
+        let value = "Pokémon™ ポケモン";
+        let entries = vec![Entry::new("AppName", Node::String(value.to_string()))];
+
+        let bytes = to_bytes(&entries);
+        assert_eq!(&bytes[9..9 + value.len()], value.as_bytes());
+        assert_eq!(entries, from_bytes(&bytes).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_utf8_strings_round_trip() {
+        let bytes = [0x01, b'k', 0x00, b'a', 0xFF, 0xC3, b'b', 0xE9, 0x00, 0x08];
+
+        let parsed = from_bytes(&bytes).unwrap();
+
+        assert_eq!(bytes.as_slice(), to_bytes(&parsed).as_slice());
+    }
+
+    #[test]
+    fn test_private_use_characters_round_trip() {
+        let value = "a\u{10FF10}b\u{10FF80}\u{10FFFF}c";
+        let mut bytes = vec![0x01, b'k', 0x00];
+        bytes.extend_from_slice(value.as_bytes());
+        bytes.extend_from_slice(&[0x00, 0x08]);
+
+        let parsed = from_bytes(&bytes).unwrap();
+        assert_eq!(bytes, to_bytes(&parsed));
+
+        let entries = vec![Entry::new("k", Node::String("\u{10FF10}".to_string()))];
+        assert_eq!(entries, from_bytes(&to_bytes(&entries)).unwrap());
+    }
+}
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+pub(crate) const TYPE_MAP: u8 = 0x00;
+pub(crate) const TYPE_STRING: u8 = 0x01;
+pub(crate) const TYPE_INT32: u8 = 0x02;
+pub(crate) const TYPE_FLOAT: u8 = 0x03;
+pub(crate) const TYPE_PTR: u8 = 0x04;
+pub(crate) const TYPE_WSTRING: u8 = 0x05;
+pub(crate) const TYPE_COLOR: u8 = 0x06;
+pub(crate) const TYPE_UINT64: u8 = 0x07;
+pub(crate) const TYPE_END: u8 = 0x08;
+pub(crate) const TYPE_INT64: u8 = 0x0A;
+
+/// A key with its value inside a binary VDF map. The order of the entries is kept.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub key: String,
+    pub value: Node,
+}
+
+impl Entry {
+    pub(crate) fn new(key: &str, value: Node) -> Self {
+        Self {
+            key: key.to_string(),
+            value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Node {
+    Map(Vec<Entry>),
+    String(String),
+    Int32(i32),
+    Float(f32),
+    Ptr(u32),
+    WString(Vec<u16>),
+    Color([u8; 4]),
+    UInt64(u64),
+    Int64(i64),
+}
+
+impl Node {
+    pub(crate) fn type_byte(&self) -> u8 {
+        match self {
+            Node::Map(_) => TYPE_MAP,
+            Node::String(_) => TYPE_STRING,
+            Node::Int32(_) => TYPE_INT32,
+            Node::Float(_) => TYPE_FLOAT,
+            Node::Ptr(_) => TYPE_PTR,
+            Node::WString(_) => TYPE_WSTRING,
+            Node::Color(_) => TYPE_COLOR,
+            Node::UInt64(_) => TYPE_UINT64,
+            Node::Int64(_) => TYPE_INT64,
+        }
+    }
+}
@@ -0,0 +1,503 @@
+pub(crate) mod node;
+pub(crate) mod text_vdf_error;
+
+use crate::vdf::text::node::{Entry, Value};
+use crate::vdf::text::text_vdf_error::{TextVdfError, TextVdfErrorKind};
+use anyhow::{Context, anyhow};
+use std::fmt::{Display, Formatter};
+use std::iter::Peekable;
+use std::path::Path;
+use std::str::Chars;
+
+const INCLUDE_DIRECTIVE: &str = "#include";
+const BASE_DIRECTIVE: &str = "#base";
+/// Deeper `#include`/`#base` chains are rejected so that include cycles terminate.
+const MAX_INCLUDE_DEPTH: usize = 8;
+/// Deeper maps are rejected so that corrupt input cannot overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+/// Reads a text VDF file and resolves its `#include` and `#base` directives and conditionals.
+pub(crate) fn from_path(path: &Path) -> anyhow::Result<Vec<Entry>> {
+    let entries = read_with_directives(path, 0)?;
+    Ok(resolve_conditionals(entries))
+}
+
+/// Parses a text VDF document. Directives and conditionals are kept as they are.
+pub(crate) fn from_str(input: &str) -> Result<Vec<Entry>, TextVdfError> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser {
+        tokens: tokens.into_iter().peekable(),
+        line: 1,
+        column: 1,
+    };
+    parser.parse_entries(0)
+}
+
+/// Removes all entries whose conditional does not hold on the current platform.
+pub(crate) fn resolve_conditionals(entries: Vec<Entry>) -> Vec<Entry> {
+    entries
+        .into_iter()
+        .filter(|e| e.condition.as_deref().is_none_or(evaluate_condition))
+        .map(|mut e| {
+            e.condition = None;
+            if let Value::Map(children) = e.value {
+                e.value = Value::Map(resolve_conditionals(children));
+            }
+            e
+        })
+        .collect()
+}
+
+fn read_with_directives(path: &Path, depth: usize) -> anyhow::Result<Vec<Entry>> {
+    if depth > MAX_INCLUDE_DEPTH {
+        return Err(anyhow!(
+            "Includes are nested too deeply at {}",
+            path.display()
+        ));
+    }
+
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Could not read {}", path.display()))?;
+    let entries =
+        from_str(&content).with_context(|| format!("Could not parse {}", path.display()))?;
+    let folder = path.parent().unwrap_or(Path::new("."));
+
+    let mut rtn = Vec::with_capacity(entries.len());
+    let mut includes = Vec::new();
+    let mut bases = Vec::new();
+    for entry in entries {
+        match (entry.key.as_str(), &entry.value) {
+            (INCLUDE_DIRECTIVE, Value::String(file)) => includes.push(folder.join(file)),
+            (BASE_DIRECTIVE, Value::String(file)) => bases.push(folder.join(file)),
+            _ => rtn.push(entry),
+        }
+    }
+
+    for include in includes {
+        rtn.extend(read_with_directives(&include, depth + 1)?);
+    }
+    for base in bases {
+        merge_base(&mut rtn, read_with_directives(&base, depth + 1)?);
+    }
+
+    Ok(rtn)
+}
+
+/// Adds the entries of a `#base` file that are not already defined, merging maps recursively.
+fn merge_base(entries: &mut Vec<Entry>, base: Vec<Entry>) {
+    for base_entry in base {
+        match entries
+            .iter_mut()
+            .find(|e| e.key.eq_ignore_ascii_case(&base_entry.key))
+        {
+            Some(Entry {
+                value: Value::Map(children),
+                ..
+            }) => {
+                if let Value::Map(base_children) = base_entry.value {
+                    merge_base(children, base_children);
+                }
+            }
+            Some(_) => {}
+            None => entries.push(base_entry),
+        }
+    }
+}
+
+/// Evaluates conditionals like `$WIN32`, `!$X360` or `$WIN32||$OSX` for the current platform.
+fn evaluate_condition(condition: &str) -> bool {
+    condition.split("||").any(|alternative| {
+        alternative.split("&&").all(|term| {
+            let term = term.trim();
+            match term.strip_prefix('!') {
+                Some(symbol) => !is_symbol_defined(symbol.trim()),
+                None => is_symbol_defined(term),
+            }
+        })
+    })
+}
+
+fn is_symbol_defined(symbol: &str) -> bool {
+    let symbol = symbol.trim_start_matches('$').to_ascii_uppercase();
+    match symbol.as_str() {
+        "WIN32" | "WINDOWS" => cfg!(target_os = "windows"),
+        "WIN64" => cfg!(all(target_os = "windows", target_pointer_width = "64")),
+        "LINUX" => cfg!(target_os = "linux"),
+        "OSX" => cfg!(target_os = "macos"),
+        "POSIX" => cfg!(target_family = "unix"),
+        _ => false,
+    }
+}
+
+enum TokenKind {
+    String(String),
+    OpenBrace,
+    CloseBrace,
+    Condition(String),
+}
+
+struct Token {
+    kind: TokenKind,
+    line: usize,
+    column: usize,
+}
+
+struct Lexer<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    column: usize,
+}
+
+impl Lexer<'_> {
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn error(&self, kind: TextVdfErrorKind) -> TextVdfError {
+        TextVdfError {
+            kind,
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    fn read_quoted(&mut self) -> Result<String, TextVdfError> {
+        let mut rtn = String::new();
+        loop {
+            match self.next_char() {
+                None => return Err(self.error(TextVdfErrorKind::UnterminatedString)),
+                Some('"') => return Ok(rtn),
+                Some('\\') => match self.next_char() {
+                    Some('n') => rtn.push('\n'),
+                    Some('t') => rtn.push('\t'),
+                    Some('\\') => rtn.push('\\'),
+                    Some('"') => rtn.push('"'),
+                    Some(c) => {
+                        rtn.push('\\');
+                        rtn.push(c);
+                    }
+                    None => return Err(self.error(TextVdfErrorKind::UnterminatedString)),
+                },
+                Some(c) => rtn.push(c),
+            }
+        }
+    }
+
+    fn read_unquoted(&mut self, first: char) -> String {
+        let mut rtn = String::from(first);
+        while let Some(&c) = self.chars.peek() {
+            if c.is_whitespace() || matches!(c, '"' | '{' | '}') {
+                break;
+            }
+            rtn.push(c);
+            self.next_char();
+        }
+        rtn
+    }
+
+    fn read_condition(&mut self) -> Result<String, TextVdfError> {
+        let mut rtn = String::new();
+        loop {
+            match self.next_char() {
+                None | Some('\n') => {
+                    return Err(self.error(TextVdfErrorKind::UnterminatedCondition));
+                }
+                Some(']') => return Ok(rtn),
+                Some(c) => rtn.push(c),
+            }
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, TextVdfError> {
+    let mut lexer = Lexer {
+        chars: input.chars().peekable(),
+        line: 1,
+        column: 1,
+    };
+    let mut rtn = Vec::new();
+
+    loop {
+        let (line, column) = (lexer.line, lexer.column);
+        let Some(c) = lexer.next_char() else {
+            return Ok(rtn);
+        };
+
+        let kind = match c {
+            c if c.is_whitespace() || c == '\u{feff}' => continue,
+            '/' if lexer.chars.peek() == Some(&'/') => {
+                while lexer.chars.peek().is_some_and(|&c| c != '\n') {
+                    lexer.next_char();
+                }
+                continue;
+            }
+            '{' => TokenKind::OpenBrace,
+            '}' => TokenKind::CloseBrace,
+            '"' => TokenKind::String(lexer.read_quoted()?),
+            '[' => TokenKind::Condition(lexer.read_condition()?),
+            c => TokenKind::String(lexer.read_unquoted(c)),
+        };
+        rtn.push(Token { kind, line, column });
+    }
+}
+
+struct Parser<I: Iterator<Item = Token>> {
+    tokens: Peekable<I>,
+    line: usize,
+    column: usize,
+}
+
+impl<I: Iterator<Item = Token>> Parser<I> {
+    fn next(&mut self) -> Option<TokenKind> {
+        let token = self.tokens.next()?;
+        self.line = token.line;
+        self.column = token.column;
+        Some(token.kind)
+    }
+
+    fn next_condition(&mut self) -> Option<String> {
+        if let Some(Token {
+            kind: TokenKind::Condition(_),
+            ..
+        }) = self.tokens.peek()
+            && let Some(TokenKind::Condition(condition)) = self.next()
+        {
+            return Some(condition);
+        }
+        None
+    }
+
+    fn error(&self, kind: TextVdfErrorKind) -> TextVdfError {
+        TextVdfError {
+            kind,
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    /// Reads the entries of a map up to and including its close brace. The root map at
+    /// `depth` 0 ends with the input instead.
+    fn parse_entries(&mut self, depth: usize) -> Result<Vec<Entry>, TextVdfError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error(TextVdfErrorKind::TooDeeplyNested));
+        }
+        let is_root = depth == 0;
+        let mut rtn = Vec::new();
+
+        loop {
+            let key = match self.next() {
+                None if is_root => return Ok(rtn),
+                None => return Err(self.error(TextVdfErrorKind::MapNotClosed)),
+                Some(TokenKind::CloseBrace) if !is_root => return Ok(rtn),
+                Some(TokenKind::CloseBrace) => {
+                    return Err(self.error(TextVdfErrorKind::UnexpectedCloseBrace));
+                }
+                Some(TokenKind::OpenBrace) => {
+                    return Err(self.error(TextVdfErrorKind::UnexpectedOpenBrace));
+                }
+                Some(TokenKind::Condition(_)) => {
+                    return Err(self.error(TextVdfErrorKind::UnexpectedCondition));
+                }
+                Some(TokenKind::String(key)) => key,
+            };
+
+            let mut condition = self.next_condition();
+            let value = match self.next() {
+                Some(TokenKind::String(value)) => Value::String(value),
+                Some(TokenKind::OpenBrace) => Value::Map(self.parse_entries(depth + 1)?),
+                _ => return Err(self.error(TextVdfErrorKind::MissingValue(key))),
+            };
+            if condition.is_none() {
+                condition = self.next_condition();
+            }
+
+            rtn.push(Entry {
+                key,
+                value,
+                condition,
+            });
+        }
+    }
+}
+
+/// Writes the entry as a text VDF document, which reads back to the same entry.
+impl Display for Entry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut out = String::new();
+        write_entries(&mut out, std::slice::from_ref(self), 0);
+        f.write_str(&out)
+    }
+}
+
+fn write_entries(out: &mut String, entries: &[Entry], depth: usize) {
+    let indent = "\t".repeat(depth);
+    for entry in entries {
+        out.push_str(&indent);
+        if entry.key == INCLUDE_DIRECTIVE || entry.key == BASE_DIRECTIVE {
+            out.push_str(&entry.key);
+        } else {
+            write_quoted(out, &entry.key);
+        }
+        match &entry.value {
+            Value::String(value) => {
+                out.push_str("\t\t");
+                write_quoted(out, value);
+                write_condition(out, &entry.condition);
+                out.push('\n');
+            }
+            Value::Map(children) => {
+                write_condition(out, &entry.condition);
+                out.push('\n');
+                out.push_str(&indent);
+                out.push_str("{\n");
+                write_entries(out, children, depth + 1);
+                out.push_str(&indent);
+                out.push_str("}\n");
+            }
+        }
+    }
+}
+
+fn write_quoted(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn write_condition(out: &mut String, condition: &Option<String>) {
+    if let Some(condition) = condition {
+        out.push_str(" [");
+        out.push_str(condition);
+        out.push(']');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::TempDir;
+    use crate::vdf::text::node::{Entry, Value};
+    use crate::vdf::text::text_vdf_error::TextVdfErrorKind;
+    use crate::vdf::text::{MAX_DEPTH, from_path, from_str, resolve_conditionals};
+
+    const EXAMPLE: &str = r#"
+// A comment
+"users"
+{
+	"76561198000000001"
+	{
+		"AccountName"		"first"
+		"PersonaName"		"Say \"Hi\"\tC:\\Games"
+		unquoted		value // trailing comment
+	}
+	"windows_only"		"1" [$WIN32]
+	"not_windows"		"1" [!$WIN32]
+	"conditional_map" [$WIN32||$LINUX||$OSX]
+	{
+	}
+}
+"#;
+
+    #[test]
+    fn test_parse_and_round_trip() {
+        let entries = from_str(EXAMPLE).unwrap();
+        let users = &entries[0].value;
+        let user = users.get("76561198000000001").unwrap();
+
+        assert_eq!(Some("first"), user.get("accountname").unwrap().as_str());
+        assert_eq!(
+            Some("Say \"Hi\"\tC:\\Games"),
+            user.get("PersonaName").unwrap().as_str()
+        );
+        assert_eq!(Some("value"), user.get("unquoted").unwrap().as_str());
+        assert_eq!(
+            Some("$WIN32".to_string()),
+            users.as_map().unwrap()[1].condition
+        );
+
+        let text: String = entries.iter().map(Entry::to_string).collect();
+        assert_eq!(entries, from_str(&text).unwrap());
+    }
+
+    #[test]
+    fn test_conditionals() {
+        let entries = resolve_conditionals(from_str(EXAMPLE).unwrap());
+        let users = &entries[0].value;
+
+        assert_eq!(
+            cfg!(target_os = "windows"),
+            users.get("windows_only").is_some()
+        );
+        assert_eq!(
+            !cfg!(target_os = "windows"),
+            users.get("not_windows").is_some()
+        );
+        assert!(users.get("conditional_map").is_some());
+    }
+
+    #[test]
+    fn test_errors_report_position() {
+        let err = from_str("\"a\"\n{\n\t\"b\"\t\t\"c\"\n").unwrap_err();
+        assert_eq!(TextVdfErrorKind::MapNotClosed, err.kind);
+        assert_eq!(3, err.line);
+
+        let err = from_str("\"a\"\n}").unwrap_err();
+        assert_eq!(TextVdfErrorKind::MissingValue("a".to_string()), err.kind);
+        assert_eq!((2, 1), (err.line, err.column));
+
+        let err = from_str("\"a\" \"b").unwrap_err();
+        assert_eq!(TextVdfErrorKind::UnterminatedString, err.kind);
+
+        let nested = "\"a\" {".repeat(MAX_DEPTH + 1) + &"}".repeat(MAX_DEPTH + 1);
+        let err = from_str(&nested).unwrap_err();
+        assert_eq!(TextVdfErrorKind::TooDeeplyNested, err.kind);
+        let nested = "\"a\" {".repeat(MAX_DEPTH) + &"}".repeat(MAX_DEPTH);
+        assert!(from_str(&nested).is_ok());
+    }
+
+    #[test]
+    fn test_include_and_base() {
+        let dir = TempDir::new("text_vdf");
+        std::fs::write(
+            dir.join("main.vdf"),
+            "#base \"base.vdf\"\n#include \"include.vdf\"\n\"root\" { \"a\" \"main\" }",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("base.vdf"),
+            "\"root\" { \"a\" \"base\" \"b\" \"base\" }",
+        )
+        .unwrap();
+        std::fs::write(dir.join("include.vdf"), "\"included\" \"1\"").unwrap();
+
+        let entries = from_path(&dir.join("main.vdf")).unwrap();
+
+        assert_eq!(
+            vec![
+                Entry::new(
+                    "root",
+                    Value::Map(vec![
+                        Entry::new("a", Value::String("main".to_string())),
+                        Entry::new("b", Value::String("base".to_string())),
+                    ])
+                ),
+                Entry::new("included", Value::String("1".to_string())),
+            ],
+            entries
+        );
+    }
+}
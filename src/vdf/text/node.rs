@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+/// A key with its value inside a text VDF map. Keys may repeat and the order is kept.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub key: String,
+    pub value: Value,
+    /// Conditional like `$WIN32` or `!$OSX` written as `[...]` behind the value.
+    pub condition: Option<String>,
+}
+
+impl Entry {
+    #[cfg(test)]
+    pub(crate) fn new(key: &str, value: Value) -> Self {
+        Self {
+            key: key.to_string(),
+            value,
+            condition: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Value {
+    String(String),
+    Map(Vec<Entry>),
+}
+
+impl Value {
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) => Some(value),
+            Value::Map(_) => None,
+        }
+    }
+
+    pub(crate) fn as_map(&self) -> Option<&[Entry]> {
+        match self {
+            Value::String(_) => None,
+            Value::Map(entries) => Some(entries),
+        }
+    }
+
+    /// Value of the first entry with the key, compared case-insensitively like Steam does.
+    pub(crate) fn get(&self, key: &str) -> Option<&Value> {
+        self.as_map()?
+            .iter()
+            .find(|e| e.key.eq_ignore_ascii_case(key))
+            .map(|e| &e.value)
+    }
+}
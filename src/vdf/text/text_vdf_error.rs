@@ -0,0 +1,50 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// An error while reading a text VDF document with the line and column (both starting at 1)
+/// it occurred at.
+#[derive(Debug)]
+pub struct TextVdfError {
+    pub kind: TextVdfErrorKind,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TextVdfErrorKind {
+    UnterminatedString,
+    UnterminatedCondition,
+    UnexpectedOpenBrace,
+    UnexpectedCloseBrace,
+    UnexpectedCondition,
+    MissingValue(String),
+    MapNotClosed,
+    TooDeeplyNested,
+}
+
+impl Display for TextVdfErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TextVdfErrorKind::UnterminatedString => write!(f, "string is not terminated"),
+            TextVdfErrorKind::UnterminatedCondition => write!(f, "condition is not terminated"),
+            TextVdfErrorKind::UnexpectedOpenBrace => write!(f, "unexpected \"{{\""),
+            TextVdfErrorKind::UnexpectedCloseBrace => write!(f, "unexpected \"}}\""),
+            TextVdfErrorKind::UnexpectedCondition => write!(f, "unexpected condition"),
+            TextVdfErrorKind::MissingValue(key) => write!(f, "key \"{key}\" has no value"),
+            TextVdfErrorKind::MapNotClosed => write!(f, "map is not closed"),
+            TextVdfErrorKind::TooDeeplyNested => write!(f, "maps are nested too deeply"),
+        }
+    }
+}
+
+impl Display for TextVdfError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at line {} column {}",
+            self.kind, self.line, self.column
+        )
+    }
+}
+
+impl Error for TextVdfError {}